@@ -1,6 +1,7 @@
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs;
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum Direction {
     N,
     E,
@@ -8,7 +9,59 @@ enum Direction {
     W,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+impl Direction {
+    fn opposite(&self) -> Direction {
+        match self {
+            Direction::N => Direction::S,
+            Direction::E => Direction::W,
+            Direction::S => Direction::N,
+            Direction::W => Direction::E,
+        }
+    }
+
+    // The direction that leads from one point to an adjacent one.
+    fn between(from: &Point, to: &Point) -> Option<Direction> {
+        match (from, to) {
+            _ if to.line + 1 == from.line && to.column == from.column => Some(Direction::N),
+            _ if to.line == from.line && to.column == from.column + 1 => Some(Direction::E),
+            _ if to.line == from.line + 1 && to.column == from.column => Some(Direction::S),
+            _ if to.line == from.line && to.column + 1 == from.column => Some(Direction::W),
+            _ => None,
+        }
+    }
+
+    // The pipe shape joining two sides of a tile, in either order.
+    fn joining(a: Direction, b: Direction) -> Option<char> {
+        let shape = match (a, b) {
+            (Direction::N, Direction::S) | (Direction::S, Direction::N) => '|',
+            (Direction::E, Direction::W) | (Direction::W, Direction::E) => '-',
+            (Direction::N, Direction::E) | (Direction::E, Direction::N) => 'L',
+            (Direction::N, Direction::W) | (Direction::W, Direction::N) => 'J',
+            (Direction::S, Direction::W) | (Direction::W, Direction::S) => '7',
+            (Direction::S, Direction::E) | (Direction::E, Direction::S) => 'F',
+            _ => return None,
+        };
+
+        Some(shape)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum MazeError {
+    NoStart,
+    NoLoop,
+}
+
+impl std::fmt::Display for MazeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MazeError::NoStart => write!(f, "The maze has no starting position 'S'"),
+            MazeError::NoLoop => write!(f, "No loop of pipes leads out of 'S' and back"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 struct Point {
     line: usize,
     column: usize,
@@ -53,180 +106,244 @@ impl Pipe {
     }
 }
 
-fn parse_path(filepath: &str) -> Vec<Pipe> {
+fn read_grid(filepath: &str) -> Vec<Vec<char>> {
     let input = fs::read_to_string(filepath)
         .expect("There was an issue reading the file at the specified path");
 
-    let mut path: Vec<Pipe> = Vec::new();
+    input.lines().map(|line| line.chars().collect()).collect()
+}
+
+fn find_start(grid: &[Vec<char>]) -> Option<Point> {
+    grid.iter().enumerate().find_map(|(line, row)| {
+        let column = row.iter().position(|c| *c == 'S')?;
+
+        Some(Point::new(line, column))
+    })
+}
+
+fn parse_path(filepath: &str) -> Result<Vec<Pipe>, MazeError> {
+    let grid = read_grid(filepath);
+    let start = find_start(&grid).ok_or(MazeError::NoStart)?;
+
+    follow_start(&grid, &start).ok_or(MazeError::NoLoop)
+}
+
+fn follow_start(grid: &[Vec<char>], start: &Point) -> Option<Vec<Pipe>> {
+    // Our starting position character does not indicate flow, so try each shape it
+    // could be. Pipes next to 'S' can point at it without being part of the loop, so
+    // a shape only fits if walking out of one end leads back in through the other.
+    const SHAPES: [(Direction, Direction); 6] = [
+        (Direction::N, Direction::E),
+        (Direction::N, Direction::S),
+        (Direction::N, Direction::W),
+        (Direction::E, Direction::S),
+        (Direction::E, Direction::W),
+        (Direction::S, Direction::W),
+    ];
+
+    SHAPES
+        .iter()
+        .find_map(|(out, back)| follow_path(grid, start, *out, *back).ok())
+}
+
+// Walks the pipes leaving `start` heading `out`, returning the loop if it comes back
+// into `start` from the `back` side. If it runs off the grid or into a pipe that does
+// not connect, the pipes walked so far are returned as the error: the walk can only
+// retrace them, so none of them can be part of a loop.
+fn follow_path(
+    grid: &[Vec<char>],
+    start: &Point,
+    out: Direction,
+    back: Direction,
+) -> Result<Vec<Pipe>, Vec<Pipe>> {
+    let mut path: Vec<Pipe> = vec![Pipe::new(
+        grid[start.line][start.column],
+        start.line,
+        start.column,
+    )];
+    let mut position = start.clone();
+    let mut traveling_to = out;
+
+    loop {
+        let next = match traveling_to {
+            Direction::N => position
+                .line
+                .checked_sub(1)
+                .map(|line| Point::new(line, position.column)),
+            Direction::E => Some(Point::new(position.line, position.column + 1)),
+            Direction::S => Some(Point::new(position.line + 1, position.column)),
+            Direction::W => position
+                .column
+                .checked_sub(1)
+                .map(|column| Point::new(position.line, column)),
+        };
 
-    for (index, line) in input.lines().enumerate() {
-        let position = line.find('S');
+        position = match next {
+            Some(next) => next,
+            None => return Err(path),
+        };
 
-        if let Some(_) = position {
-            path.push(Pipe::new('S', index, position.unwrap()));
-            break;
+        // Stop once we have made it back to the start
+        if position == *start {
+            return if traveling_to.opposite() == back {
+                Ok(path)
+            } else {
+                Err(path)
+            };
         }
+
+        let next_pipe = match grid
+            .get(position.line)
+            .and_then(|row| row.get(position.column))
+        {
+            Some(shape) => Pipe::new(*shape, position.line, position.column),
+            None => return Err(path),
+        };
+
+        traveling_to = match next_pipe.from(&traveling_to.opposite()) {
+            Some(direction) => direction,
+            None => return Err(path),
+        };
+        path.push(next_pipe);
     }
+}
 
-    // Once the starting position is set, check the adjacent positions for
-    // pipes, since our starting position character does not indicate flow.
-    let lines: Vec<&str> = input.lines().collect();
-    let starting_row = path[0].position.line;
-    let starting_column = path[0].position.column;
-    let mut traveled_from: Direction;
-
-    'initial_direction: loop {
-        // Check top
-        if starting_row > 0 {
-            match lines[starting_row - 1]
-                .chars()
-                .nth(starting_column)
-                .expect("Position mismatch")
-            {
-                c if c == '|' || c == '7' || c == 'F' => {
-                    path.push(Pipe::new(c, starting_row - 1, starting_column));
-                    traveled_from = Direction::S;
-                    break 'initial_direction;
-                }
-                _ => (),
-            }
-        }
+// Every closed loop of pipes in the grid, the one through 'S' first. A pipe has only
+// two ends, so walking from any pipe either comes back around or dead-ends, and
+// every pipe passed on the way shares that fate and needs no walk of its own.
+fn find_loops(filepath: &str) -> Vec<Vec<Pipe>> {
+    let grid = read_grid(filepath);
+    let mut visited: HashSet<Point> = HashSet::new();
+    let mut loops: Vec<Vec<Pipe>> = Vec::new();
+
+    if let Some(path) = find_start(&grid).and_then(|start| follow_start(&grid, &start)) {
+        visited.extend(path.iter().map(|pipe| pipe.position.clone()));
+        loops.push(path);
+    }
 
-        // Check right
-        if starting_column < lines[starting_row].len() - 1 {
-            match lines[starting_row]
-                .chars()
-                .nth(starting_column + 1)
-                .expect("Position mismatch")
-            {
-                c if c == '-' || c == '7' || c == 'J' => {
-                    path.push(Pipe::new(c, starting_row, starting_column + 1));
-                    traveled_from = Direction::W;
-                    break 'initial_direction;
-                }
-                _ => (),
+    for (line, row) in grid.iter().enumerate() {
+        for (column, shape) in row.iter().enumerate() {
+            let start = Point::new(line, column);
+
+            if visited.contains(&start) {
+                continue;
             }
-        }
 
-        // Check bottom
-        if starting_row < lines.len() - 1 {
-            match lines[starting_row + 1]
-                .chars()
-                .nth(starting_column)
-                .expect("Position mismatch")
-            {
-                c if c == '|' || c == 'L' || c == 'J' => {
-                    path.push(Pipe::new(c, starting_row + 1, starting_column));
-                    traveled_from = Direction::N;
-                    break 'initial_direction;
-                }
-                _ => (),
+            let pipe = Pipe::new(*shape, line, column);
+            let ends: Vec<Direction> = [Direction::N, Direction::E, Direction::S, Direction::W]
+                .into_iter()
+                .filter(|side| pipe.from(side).is_some())
+                .collect();
+
+            if ends.len() != 2 {
+                continue;
             }
-        }
 
-        // Check left
-        if starting_column > 0 {
-            match lines[starting_row]
-                .chars()
-                .nth(starting_column - 1)
-                .expect("Position mismatch")
-            {
-                c if c == '-' || c == 'L' || c == 'F' => {
-                    path.push(Pipe::new(c, starting_row, starting_column - 1));
-                    traveled_from = Direction::E;
-                    break 'initial_direction;
+            match follow_path(&grid, &start, ends[0], ends[1]) {
+                Ok(path) => {
+                    visited.extend(path.iter().map(|pipe| pipe.position.clone()));
+                    loops.push(path);
+                }
+                Err(dead_end) => {
+                    visited.extend(dead_end.iter().map(|pipe| pipe.position.clone()));
                 }
-                _ => (),
             }
         }
     }
 
-    let mut pipe_counter: usize = 1;
-
-    'follow_path: loop {
-        let current_pipe: &Pipe = &path[pipe_counter];
+    loops
+}
 
-        // Find the next pipe
-        let traveling_to = current_pipe
-            .from(&traveled_from)
-            .expect("There was an error in the pipe loop");
+#[derive(Debug, PartialEq, Eq)]
+struct LoopReport {
+    start: Point,
+    length: usize,
+    farthest: usize,
+    // Every tile inside the loop, including those of loops nested in it
+    enclosed: u32,
+    // Only the tiles inside the loop that are not part of or inside a nested loop
+    exclusive: u32,
+    // The innermost loop this one sits inside, by index, and how many surround it
+    parent: Option<usize>,
+    depth: usize,
+}
 
-        let next_point = match traveling_to {
-            Direction::N => {
-                Point::new(current_pipe.position.line - 1, current_pipe.position.column)
+fn report_loops(grid: &str, loops: &[Vec<Pipe>]) -> Vec<LoopReport> {
+    let interiors: Vec<HashSet<Point>> = loops
+        .iter()
+        .map(|path| find_interior_points(grid, path))
+        .collect();
+    let enclosed: Vec<u32> = loops
+        .iter()
+        .map(|path| count_interior_points_by_area(path))
+        .collect();
+
+    // Loops cannot cross, so one loop is inside another if any of its pipes are
+    let surrounding: Vec<Vec<usize>> = loops
+        .iter()
+        .map(|path| {
+            (0..loops.len())
+                .filter(|other| interiors[*other].contains(&path[0].position))
+                .collect()
+        })
+        .collect();
+    let parents: Vec<Option<usize>> = surrounding
+        .iter()
+        .map(|others| others.iter().copied().min_by_key(|other| enclosed[*other]))
+        .collect();
+
+    loops
+        .iter()
+        .enumerate()
+        .map(|(index, path)| {
+            let nested: u32 = (0..loops.len())
+                .filter(|child| parents[*child] == Some(index))
+                .map(|child| loops[child].len() as u32 + enclosed[child])
+                .sum();
+
+            LoopReport {
+                start: path[0].position.clone(),
+                length: path.len(),
+                farthest: path.len() / 2,
+                enclosed: enclosed[index],
+                exclusive: enclosed[index] - nested,
+                parent: parents[index],
+                depth: surrounding[index].len(),
             }
-            Direction::E => {
-                Point::new(current_pipe.position.line, current_pipe.position.column + 1)
-            }
-            Direction::S => {
-                Point::new(current_pipe.position.line + 1, current_pipe.position.column)
-            }
-            Direction::W => {
-                Point::new(current_pipe.position.line, current_pipe.position.column - 1)
-            }
-        };
-
-        let next_pipe = Pipe {
-            shape: lines[next_point.line]
-                .chars()
-                .nth(next_point.column)
-                .expect("Point out of bounds of possible characters"),
-            position: next_point,
-        };
+        })
+        .collect()
+}
 
-        // Break out of the loop if we have made it back to the start
-        if next_pipe.shape == 'S' {
-            break 'follow_path;
-        }
+// The shape hidden under 'S', from the pipes on either side of it in the path,
+// whichever way round the path was walked.
+fn start_shape(path: &[Pipe]) -> char {
+    let start = &path[0].position;
+    let first = Direction::between(start, &path[1].position);
+    let last = Direction::between(start, &path[path.len() - 1].position);
 
-        // Push to our path vector and reassign pointers
-        path.push(next_pipe);
-        pipe_counter += 1;
-        traveled_from = match traveling_to {
-            Direction::N => Direction::S,
-            Direction::E => Direction::W,
-            Direction::S => Direction::N,
-            Direction::W => Direction::E,
-        };
+    match (first, last) {
+        (Some(first), Some(last)) => Direction::joining(first, last),
+        _ => None,
     }
-
-    path
+    .expect("Path was found to be disjoint")
 }
 
-fn count_interior_points(grid: String, pipe_path: &mut Vec<Pipe>) -> u32 {
-    // In order to not have to guess about the shape of the 'S' pipe,
-    //   we'll replace it here with the corresponding shape.
-    // To not alter the data in a way that's unexpected, we'll sacrifice
-    //   some memory to clone `path`.
-    let mut path = pipe_path.clone();
-
-    let direction_a = match path[1].position {
-        Point { line, .. } if line == path[0].position.line - 1 => Direction::N,
-        Point { line: _, column } if column == path[0].position.column + 1 => Direction::E,
-        Point { line, .. } if line == path[0].position.line + 1 => Direction::S,
-        Point { line: _, column } if column == path[0].position.column - 1 => Direction::W,
-        _ => panic!("Path was found to be disjoint"),
-    };
-
-    let direction_b = match path[path.len() - 1].position {
-        Point { line, .. } if line == path[0].position.line - 1 => Direction::N,
-        Point { line: _, column } if column == path[0].position.column + 1 => Direction::E,
-        Point { line, .. } if line == path[0].position.line + 1 => Direction::S,
-        Point { line: _, column } if column == path[0].position.column - 1 => Direction::W,
-        _ => panic!("Path was found to be disjoint"),
-    };
-
-    // Since we started the discovery from the top of 'S', there are only six valid combinations:
-    path[0].shape = match (direction_a, direction_b) {
-        (Direction::N, Direction::E) => 'L',
-        (Direction::N, Direction::S) => '|',
-        (Direction::N, Direction::W) => 'J',
-        (Direction::E, Direction::S) => 'F',
-        (Direction::E, Direction::W) => '-',
-        (Direction::S, Direction::W) => '7',
-        _ => panic!("Encountered a combination of Directions that should be impossible"),
-    };
+// The loop's pipes by position, with 'S' swapped for the shape it stands in for.
+fn loop_shapes(path: &[Pipe]) -> HashMap<(usize, usize), char> {
+    let mut shapes: HashMap<(usize, usize), char> = path
+        .iter()
+        .map(|pipe| ((pipe.position.line, pipe.position.column), pipe.shape))
+        .collect();
+
+    shapes.insert(
+        (path[0].position.line, path[0].position.column),
+        start_shape(path),
+    );
+
+    shapes
+}
 
+fn find_interior_points(grid: &str, path: &[Pipe]) -> HashSet<Point> {
     // Let's establish what we can that is concrete:
     //   1. If we scan from left to right for each line, the
     //      first pipe that we hit will be an outer wall of
@@ -236,19 +353,19 @@ fn count_interior_points(grid: String, pipe_path: &mut Vec<Pipe>) -> u32 {
     //   3. We can set a flag to signal if we should count a given
     //      point based on the conclusions we can draw about the
     //      overall shape from the first pipe we encounter.
-    let mut counter = 0;
+    //   4. Looking pipes up by position keeps each check constant time
+    //      instead of a walk over the whole path.
+    let shapes = loop_shapes(path);
+    let mut interior: HashSet<Point> = HashSet::new();
 
     for (line_index, line) in grid.lines().enumerate() {
         let mut previous_corner = '.';
         let mut is_interior = false;
-        let mut accumulator = 0;
+        let mut accumulator: Vec<Point> = Vec::new();
 
-        for (c_index, c) in line.chars().enumerate() {
+        for c_index in 0..line.len() {
             // If c is a pipe in the path...
-            if path
-                .iter()
-                .any(|x| x.position.line == line_index && x.position.column == c_index)
-            {
+            if let Some(c) = shapes.get(&(line_index, c_index)) {
                 // ...toggle the flag based on the shape of the pipe.
                 match c {
                     '|' => is_interior = !is_interior,
@@ -264,27 +381,305 @@ fn count_interior_points(grid: String, pipe_path: &mut Vec<Pipe>) -> u32 {
                 }
 
                 if !is_interior {
-                    counter += accumulator;
-                    accumulator = 0;
+                    interior.extend(accumulator.drain(..));
+                }
+            } else if is_interior {
+                accumulator.push(Point::new(line_index, c_index));
+            }
+        }
+    }
+
+    interior
+}
+
+fn count_interior_points(grid: String, pipe_path: &[Pipe]) -> u32 {
+    find_interior_points(&grid, pipe_path).len() as u32
+}
+
+// The same count from the loop's geometry alone. The shoelace formula gives the
+// area enclosed by the pipe centres, and Pick's theorem (A = I + B / 2 - 1) turns
+// that area and the number of pipes on the boundary into the number of tiles inside.
+fn count_interior_points_by_area(path: &[Pipe]) -> u32 {
+    let twice_area: i64 = path
+        .iter()
+        .zip(path.iter().cycle().skip(1))
+        .map(|(a, b)| {
+            let (x1, y1) = (a.position.column as i64, a.position.line as i64);
+            let (x2, y2) = (b.position.column as i64, b.position.line as i64);
+
+            x1 * y2 - x2 * y1
+        })
+        .sum::<i64>()
+        .abs();
+
+    ((twice_area - path.len() as i64) / 2 + 1) as u32
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Tile {
+    Loop,
+    Reachable,
+    Enclosed,
+}
+
+// Classifies every tile by letting water in from the edges of the map. Each tile is
+// blown up into a 3x3 block where a pipe fills the centre and the sides it connects,
+// so the gaps between two pipes that run side by side become cells the water can
+// squeeze through. Whatever stays dry inside the loop is enclosed.
+fn classify_tiles(grid: &str, path: &[Pipe]) -> Vec<Vec<Tile>> {
+    const SCALE: usize = 3;
+
+    let shapes = loop_shapes(path);
+    let height = grid.lines().count();
+    let width = grid.lines().map(|line| line.len()).max().unwrap_or(0);
+    let mut blocked = vec![vec![false; width * SCALE]; height * SCALE];
+
+    for ((line, column), shape) in &shapes {
+        let (centre_line, centre_column) = (line * SCALE + 1, column * SCALE + 1);
+
+        blocked[centre_line][centre_column] = true;
+
+        for side in [Direction::N, Direction::E, Direction::S, Direction::W] {
+            if Pipe::new(*shape, *line, *column).from(&side).is_some() {
+                match side {
+                    Direction::N => blocked[centre_line - 1][centre_column] = true,
+                    Direction::E => blocked[centre_line][centre_column + 1] = true,
+                    Direction::S => blocked[centre_line + 1][centre_column] = true,
+                    Direction::W => blocked[centre_line][centre_column - 1] = true,
                 }
+            }
+        }
+    }
+
+    let mut reached = vec![vec![false; width * SCALE]; height * SCALE];
+    let mut queue: VecDeque<(usize, usize)> = VecDeque::new();
+
+    for line in 0..height * SCALE {
+        for column in 0..width * SCALE {
+            let on_border = line == 0
+                || column == 0
+                || line == height * SCALE - 1
+                || column == width * SCALE - 1;
+
+            if on_border && !blocked[line][column] {
+                reached[line][column] = true;
+                queue.push_back((line, column));
+            }
+        }
+    }
+
+    while let Some((line, column)) = queue.pop_front() {
+        let neighbours = [
+            (line.wrapping_sub(1), column),
+            (line, column + 1),
+            (line + 1, column),
+            (line, column.wrapping_sub(1)),
+        ];
+
+        for (line, column) in neighbours {
+            if line < height * SCALE
+                && column < width * SCALE
+                && !blocked[line][column]
+                && !reached[line][column]
+            {
+                reached[line][column] = true;
+                queue.push_back((line, column));
+            }
+        }
+    }
+
+    (0..height)
+        .map(|line| {
+            (0..width)
+                .map(|column| {
+                    if shapes.contains_key(&(line, column)) {
+                        Tile::Loop
+                    } else if reached[line * SCALE + 1][column * SCALE + 1] {
+                        Tile::Reachable
+                    } else {
+                        Tile::Enclosed
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn count_enclosed_tiles(grid: &str, path: &[Pipe]) -> u32 {
+    classify_tiles(grid, path)
+        .iter()
+        .flatten()
+        .filter(|tile| **tile == Tile::Enclosed)
+        .count() as u32
+}
+
+fn box_drawing(shape: char) -> char {
+    match shape {
+        '|' => '│',
+        '-' => '─',
+        'F' => '┌',
+        '7' => '┐',
+        'L' => '└',
+        'J' => '┘',
+        _ => shape,
+    }
+}
+
+// Draws the maze for a terminal: the loop in box-drawing characters, every other
+// pipe dimmed, and the tiles the loop encloses shaded in.
+fn render_ansi(grid: &str, path: &[Pipe]) -> String {
+    const DIM: &str = "\x1b[2m";
+    const SHADE: &str = "\x1b[33m";
+    const RESET: &str = "\x1b[0m";
+
+    let shapes = loop_shapes(path);
+    let interior = find_interior_points(grid, path);
+    let mut rendered = String::new();
+
+    for (line_index, line) in grid.lines().enumerate() {
+        for (c_index, c) in line.chars().enumerate() {
+            if let Some(shape) = shapes.get(&(line_index, c_index)) {
+                rendered.push(box_drawing(*shape));
+            } else if interior.contains(&Point::new(line_index, c_index)) {
+                rendered.push_str(&format!("{}▒{}", SHADE, RESET));
             } else {
-                if is_interior {
-                    accumulator += 1;
+                rendered.push_str(&format!("{}{}{}", DIM, box_drawing(c), RESET));
+            }
+        }
+
+        rendered.push('\n');
+    }
+
+    rendered
+}
+
+// Draws the same picture as an SVG, one unit per tile, with each pipe drawn from the
+// centre of its tile out to the sides it connects.
+fn render_svg(grid: &str, path: &[Pipe]) -> String {
+    const TILE: usize = 10;
+
+    let shapes = loop_shapes(path);
+    let interior = find_interior_points(grid, path);
+    let height = grid.lines().count();
+    let width = grid.lines().map(|line| line.len()).max().unwrap_or(0);
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+        width * TILE,
+        height * TILE,
+        width * TILE,
+        height * TILE
+    );
+
+    svg.push_str("  <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n");
+
+    for (line_index, line) in grid.lines().enumerate() {
+        for (c_index, c) in line.chars().enumerate() {
+            let (x, y) = (c_index * TILE, line_index * TILE);
+            let (centre_x, centre_y) = (x + TILE / 2, y + TILE / 2);
+
+            if interior.contains(&Point::new(line_index, c_index)) {
+                svg.push_str(&format!(
+                    "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"gold\"/>\n",
+                    x, y, TILE, TILE
+                ));
+            }
+
+            let (shape, stroke) = match shapes.get(&(line_index, c_index)) {
+                Some(shape) => (*shape, "stroke=\"black\" stroke-width=\"2\""),
+                None => (c, "stroke=\"lightgrey\" stroke-width=\"1\""),
+            };
+
+            for side in [Direction::N, Direction::E, Direction::S, Direction::W] {
+                if Pipe::new(shape, line_index, c_index).from(&side).is_none() {
+                    continue;
                 }
+
+                let (end_x, end_y) = match side {
+                    Direction::N => (centre_x, y),
+                    Direction::E => (x + TILE, centre_y),
+                    Direction::S => (centre_x, y + TILE),
+                    Direction::W => (x, centre_y),
+                };
+
+                svg.push_str(&format!(
+                    "  <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" {} stroke-linecap=\"square\"/>\n",
+                    centre_x, centre_y, end_x, end_y, stroke
+                ));
             }
         }
     }
 
-    counter
+    svg.push_str("</svg>\n");
+
+    svg
 }
 
 fn main() {
-    let mut path = parse_path("input.txt");
+    let path = parse_path("input.txt").unwrap_or_else(|error| panic!("{}", error));
 
-    let interior_points =
-        count_interior_points(fs::read_to_string("input.txt").unwrap(), &mut path);
+    let interior_points = count_interior_points(fs::read_to_string("input.txt").unwrap(), &path);
 
     println!("The number of interior points is: {:?}", interior_points);
+
+    // --render draws the maze in the terminal, --svg <file> writes it out as an image
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let grid = fs::read_to_string("input.txt").unwrap();
+
+    // --all-loops reports on every loop in the grid, not just the one through 'S'
+    if args.iter().any(|arg| arg == "--all-loops") {
+        let loops = find_loops("input.txt");
+
+        for (index, report) in report_loops(&grid, &loops).iter().enumerate() {
+            println!(
+                "Loop {} from line {}, column {}: {} pipes, farthest point {} steps away, {} tiles enclosed ({} outside nested loops), nested {} deep",
+                index + 1,
+                report.start.line,
+                report.start.column,
+                report.length,
+                report.farthest,
+                report.enclosed,
+                report.exclusive,
+                report.depth
+            );
+        }
+    }
+
+    // --flood counts by flood fill instead, pointing out any tile the two methods
+    // disagree on
+    if args.iter().any(|arg| arg == "--flood") {
+        let interior = find_interior_points(&grid, &path);
+
+        for (line, tiles) in classify_tiles(&grid, &path).iter().enumerate() {
+            for (column, tile) in tiles.iter().enumerate() {
+                let scanned = interior.contains(&Point::new(line, column));
+
+                if scanned != (*tile == Tile::Enclosed) {
+                    eprintln!(
+                        "Line {}, column {} is {:?} by flood fill but {} by scanning",
+                        line,
+                        column,
+                        tile,
+                        if scanned { "interior" } else { "exterior" }
+                    );
+                }
+            }
+        }
+
+        println!(
+            "The number of enclosed tiles by flood fill is: {:?}",
+            count_enclosed_tiles(&grid, &path)
+        );
+    }
+
+    if args.iter().any(|arg| arg == "--render") {
+        print!("{}", render_ansi(&grid, &path));
+    }
+
+    if let Some(index) = args.iter().position(|arg| arg == "--svg") {
+        let filepath = args.get(index + 1).expect("--svg needs a file to write to");
+
+        fs::write(filepath, render_svg(&grid, &path)).expect("There was an error writing the SVG");
+    }
 }
 
 #[cfg(test)]
@@ -340,16 +735,168 @@ mod tests {
             Pipe::new('|', 2, 1),
         ];
 
-        assert_eq!(expected, parse_path("test1.txt"))
+        assert_eq!(Ok(expected), parse_path("test1.txt"))
     }
 
     #[test]
     fn counts_interior_points() {
-        let mut path = parse_path("test2.txt");
+        let path = parse_path("test2.txt").unwrap();
 
         assert_eq!(
             8,
-            count_interior_points(fs::read_to_string("test2.txt").unwrap(), &mut path)
+            count_interior_points(fs::read_to_string("test2.txt").unwrap(), &path)
         )
     }
+
+    #[test]
+    fn counts_interior_points_by_area() {
+        for (filepath, expected) in [("test1.txt", 4), ("test2.txt", 8), ("test3.txt", 10)] {
+            let path = parse_path(filepath).unwrap();
+
+            assert_eq!(expected, count_interior_points_by_area(&path));
+            assert_eq!(
+                expected,
+                count_interior_points(fs::read_to_string(filepath).unwrap(), &path)
+            );
+        }
+    }
+
+    #[test]
+    fn ignores_pipes_leading_nowhere() {
+        let path = parse_path("test4.txt").unwrap();
+
+        assert_eq!(8, path.len());
+        assert_eq!('F', start_shape(&path));
+        assert_eq!(
+            1,
+            count_interior_points(fs::read_to_string("test4.txt").unwrap(), &path)
+        );
+        assert_eq!(Err(MazeError::NoLoop), parse_path("test5.txt"));
+    }
+
+    #[test]
+    fn returns_dead_ends_walked() {
+        let grid: Vec<Vec<char>> = ["F-7", "|.|", "L-."]
+            .iter()
+            .map(|line| line.chars().collect())
+            .collect();
+        let expected = vec![
+            Pipe::new('F', 0, 0),
+            Pipe::new('-', 0, 1),
+            Pipe::new('7', 0, 2),
+            Pipe::new('|', 1, 2),
+        ];
+
+        assert_eq!(
+            Err(expected),
+            follow_path(&grid, &Point::new(0, 0), Direction::E, Direction::S)
+        );
+    }
+
+    #[test]
+    fn counts_interior_points_either_way_round() {
+        let mut path = parse_path("test3.txt").unwrap();
+
+        path[1..].reverse();
+
+        assert_eq!('7', start_shape(&path));
+        assert_eq!(
+            10,
+            count_interior_points(fs::read_to_string("test3.txt").unwrap(), &path)
+        );
+    }
+
+    #[test]
+    fn renders_loop() {
+        let grid = fs::read_to_string("test4.txt").unwrap();
+        let path = parse_path("test4.txt").unwrap();
+        let rendered = render_ansi(&grid, &path);
+        let lines: Vec<&str> = rendered.lines().collect();
+
+        assert_eq!("\x1b[2m.\x1b[0m\x1b[2m─\x1b[0m┌─┐", lines[1]);
+        assert_eq!("\x1b[2m.\x1b[0m\x1b[2m.\x1b[0m│\x1b[33m▒\x1b[0m│", lines[2]);
+
+        let svg = render_svg(&grid, &path);
+
+        assert!(
+            svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"50\" height=\"40\"")
+        );
+        assert_eq!(1, svg.matches("fill=\"gold\"").count());
+        assert_eq!(16, svg.matches("stroke=\"black\"").count());
+        assert_eq!(6, svg.matches("stroke=\"lightgrey\"").count());
+    }
+
+    #[test]
+    fn flood_fill_agrees_with_scanning() {
+        for (filepath, expected) in [
+            ("test1.txt", 4),
+            ("test2.txt", 8),
+            ("test3.txt", 10),
+            ("test4.txt", 1),
+        ] {
+            let grid = fs::read_to_string(filepath).unwrap();
+            let path = parse_path(filepath).unwrap();
+            let interior = find_interior_points(&grid, &path);
+
+            assert_eq!(expected, count_enclosed_tiles(&grid, &path));
+
+            for (line, tiles) in classify_tiles(&grid, &path).iter().enumerate() {
+                for (column, tile) in tiles.iter().enumerate() {
+                    assert_eq!(
+                        interior.contains(&Point::new(line, column)),
+                        *tile == Tile::Enclosed
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn squeezes_between_pipes() {
+        let grid = fs::read_to_string("test1.txt").unwrap();
+        let tiles = classify_tiles(&grid, &parse_path("test1.txt").unwrap());
+
+        // The tiles marked O in the puzzle are only reachable between two pipes
+        assert_eq!(Tile::Reachable, tiles[3][3]);
+        assert_eq!(Tile::Enclosed, tiles[6][2]);
+        assert_eq!(Tile::Loop, tiles[1][1]);
+    }
+
+    #[test]
+    fn reports_nested_loops() {
+        let grid = fs::read_to_string("test6.txt").unwrap();
+        let loops = find_loops("test6.txt");
+        let expected = vec![
+            LoopReport {
+                start: Point::new(0, 0),
+                length: 20,
+                farthest: 10,
+                enclosed: 15,
+                exclusive: 6,
+                parent: None,
+                depth: 0,
+            },
+            LoopReport {
+                start: Point::new(1, 2),
+                length: 8,
+                farthest: 4,
+                enclosed: 1,
+                exclusive: 1,
+                parent: Some(0),
+                depth: 1,
+            },
+            LoopReport {
+                start: Point::new(5, 1),
+                length: 4,
+                farthest: 2,
+                enclosed: 0,
+                exclusive: 0,
+                parent: None,
+                depth: 0,
+            },
+        ];
+
+        assert_eq!(parse_path("test6.txt").unwrap(), loops[0]);
+        assert_eq!(expected, report_loops(&grid, &loops));
+    }
 }
@@ -2,12 +2,12 @@ use std::fs;
 
 #[derive(Debug, PartialEq, Eq)]
 struct Point {
-    line: usize,
-    column: usize
+    line: u128,
+    column: u128
 }
 
 impl Point {
-    fn new(line: usize, column: usize) -> Self {
+    fn new(line: u128, column: u128) -> Self {
         Self {
             line, column
         }
@@ -28,94 +28,269 @@ fn parse_image(path: &str) -> Vec<String> {
     image
 }
 
-// Galaxy expansion occurs where all elements in a row or column
-// are empty space (.).
-fn add_galaxy_expansion(image: &mut Vec<String>) {
-    // We iterate in reverse so we can modify without worrying about altering
-    // the upcoming indices
-    for (index, row) in image.clone().iter().enumerate().rev() {
-        if !row.contains('#') {
-            // We will use the unused character '-' to represent the million empty rows.
-            image[index] = row.replace('.', "-");
+fn locate_galaxies(image: &[String]) -> Vec<Point> {
+    let mut galaxies: Vec<Point> = Vec::new();
+
+    for (line_index, row) in image.iter().enumerate() {
+        for (column_index, ch) in row.chars().enumerate() {
+            if let '#' = ch {
+                galaxies.push(Point::new(line_index as u128, column_index as u128));
+            }
         }
     }
 
-    for i in (0..image[0].len()).rev() {
-        // For columns, we can iterate over all rows and check the nth character
-        // in each row. If we encounter a galaxy (#), continue to the next index.
-        if image.iter().any(|s| s.chars().nth(i).expect("All elements in the image should be of equal length") == '#') {
-            continue;
+    galaxies
+}
+
+// The galaxies as they appear in the image, along with the rows and columns
+// that hold no galaxies at all, which are the ones that expand.
+#[derive(Debug, PartialEq, Eq)]
+struct Universe {
+    galaxies: Vec<Point>,
+    empty_rows: Vec<u128>,
+    empty_columns: Vec<u128>
+}
+
+impl Universe {
+    fn new(image: &[String]) -> Self {
+        let galaxies = locate_galaxies(image);
+        let height = image.len();
+        let width = image.iter().map(|row| row.len()).max().unwrap_or(0);
+
+        // Mark the rows and columns holding a galaxy in one pass, then keep the rest
+        let mut occupied_rows = vec![false; height];
+        let mut occupied_columns = vec![false; width];
+
+        for galaxy in &galaxies {
+            occupied_rows[galaxy.line as usize] = true;
+            occupied_columns[galaxy.column as usize] = true;
         }
 
-        // If we made it through the above guard clause, replace the column's characters.
-        // We will use '!' to represent the million empty columns, and 'X' to represent the
-        // intersection of '!' and '-'
-        for s in image.iter_mut() {
-            match s.chars().nth(i) {
-                Some('.') => s.replace_range(i..i + 1, "!"),
-                Some('-') => s.replace_range(i..i + 1, "X"),
-                _ => (),
-            }
+        let empty_rows = (0..height).filter(|line| !occupied_rows[*line]).map(|line| line as u128).collect();
+        let empty_columns = (0..width).filter(|column| !occupied_columns[*column]).map(|column| column as u128).collect();
+
+        Self {
+            galaxies, empty_rows, empty_columns
         }
     }
+
+    // Where each galaxy ends up once every empty row and column has grown to
+    // `factor` rows or columns, so a factor of 1 leaves the image as it is.
+    fn expand(&self, factor: u64) -> Vec<Point> {
+        self.expand_unevenly(factor, factor)
+    }
+
+    // As `expand`, but with empty rows and empty columns growing by different factors.
+    fn expand_unevenly(&self, row_factor: u64, column_factor: u64) -> Vec<Point> {
+        let row_growth = (row_factor as u128).saturating_sub(1);
+        let column_growth = (column_factor as u128).saturating_sub(1);
+
+        self.galaxies.iter().map(|galaxy| {
+            // The empty lines are in order, so the ones before a galaxy are found by bisecting
+            let rows_before = self.empty_rows.partition_point(|line| *line < galaxy.line) as u128;
+            let columns_before = self.empty_columns.partition_point(|column| *column < galaxy.column) as u128;
+
+            Point::new(galaxy.line + rows_before * row_growth, galaxy.column + columns_before * column_growth)
+        }).collect()
+    }
 }
 
-fn locate_galaxies(image: &Vec<String>) -> Vec<Point> {
-    let mut galaxies: Vec<Point> = Vec::new();
+fn distance(a: &Point, b: &Point) -> u128 {
+    let horizontal_travel = a.column.abs_diff(b.column);
+    let vertical_travel = a.line.abs_diff(b.line);
 
-    // Account for the additional distance indicated by our new expansion characters
-    // '-', '!', and 'X'
-    let mut millions_of_rows: usize = 0;
+    horizontal_travel + vertical_travel
+}
 
-    for (line_index, row) in image.iter().enumerate() {
-        let mut millions_of_columns: usize = 0;
-        let mut increment_mil_rows = false;
+// Visits every pair, so it is only kept to check the sorted sum against.
+#[cfg(test)]
+fn calculate_sum_of_paths(galaxies: &[Point]) -> u128 {
+    let mut sum = 0;
 
-        for (column_index, ch) in row.chars().enumerate() {
-            match ch {
-                '!' => millions_of_columns += 1,
-                '-' => increment_mil_rows = true,
-                'X' => {
-                    millions_of_columns += 1;
-                    increment_mil_rows = true;
-                },
-                '#' => galaxies.push(Point::new(line_index + millions_of_rows * 999_999, column_index + millions_of_columns * 999_999)),
-                _ => (),
-            }
+    for i in 0..galaxies.len().saturating_sub(1) {
+        for j in i + 1..galaxies.len() {
+            sum += distance(&galaxies[i], &galaxies[j]);
         }
+    }
+
+    sum
+}
 
-        if increment_mil_rows {
-            millions_of_rows += 1;
+// The same sum without visiting every pair. Lines and columns add up separately,
+// and once they are sorted each value is the far end of a path from every value
+// before it, contributing value * index minus the sum of those earlier values.
+fn calculate_sum_of_paths_sorted(galaxies: &[Point]) -> u128 {
+    let sum_along = |mut values: Vec<u128>| {
+        values.sort_unstable();
+
+        let mut prefix_sum = 0;
+        let mut sum = 0;
+
+        for (index, value) in values.iter().enumerate() {
+            sum += value * index as u128 - prefix_sum;
+            prefix_sum += value;
+        }
+
+        sum
+    };
+
+    sum_along(galaxies.iter().map(|galaxy| galaxy.line).collect())
+        + sum_along(galaxies.iter().map(|galaxy| galaxy.column).collect())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Metric {
+    Manhattan,
+    Chebyshev,
+    Euclidean
+}
+
+impl Metric {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "manhattan" => Some(Metric::Manhattan),
+            "chebyshev" => Some(Metric::Chebyshev),
+            "euclidean" => Some(Metric::Euclidean),
+            _ => None,
         }
     }
 
-    galaxies
+    fn length(&self, a: &Point, b: &Point) -> Length {
+        match self {
+            Metric::Manhattan => Length::Whole(distance(a, b)),
+            Metric::Chebyshev => Length::Whole(chebyshev_distance(a, b)),
+            Metric::Euclidean => Length::Real(euclidean_distance(a, b)),
+        }
+    }
 }
 
-fn calculate_sum_of_paths(galaxies: &Vec<Point>) -> usize {
-    let mut sum = 0;
+// A path length under one of the metrics. Lengths from the same metric are always
+// the same variant, so they can be compared with each other.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+enum Length {
+    Whole(u128),
+    Real(f64)
+}
 
-    for i in 0..galaxies.len() - 1 {
-        for j in i + 1..galaxies.len() {
-            let Point { line: line_i, column: column_i } = galaxies[i];
-            let Point { line: line_j, column: column_j } = galaxies[j];
+impl std::fmt::Display for Length {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Length::Whole(length) => write!(f, "{}", length),
+            Length::Real(length) => write!(f, "{:.3}", length),
+        }
+    }
+}
 
-            let horizontal_travel = std::cmp::max(column_i, column_j) - std::cmp::min(column_i, column_j);
-            let vertical_travel = std::cmp::max(line_i, line_j) - std::cmp::min(line_i, line_j);
+// The number of moves between two galaxies when diagonal steps are allowed.
+fn chebyshev_distance(a: &Point, b: &Point) -> u128 {
+    std::cmp::max(a.column.abs_diff(b.column), a.line.abs_diff(b.line))
+}
 
-            sum += horizontal_travel + vertical_travel;
+fn euclidean_distance(a: &Point, b: &Point) -> f64 {
+    (a.column.abs_diff(b.column) as f64).hypot(a.line.abs_diff(b.line) as f64)
+}
+
+// Turning the grid by 45 degrees turns Chebyshev paths into Manhattan paths of
+// twice the length, since max(|x|, |y|) = (|x + y| + |x - y|) / 2. The widest
+// column is added to keep line - column from going below zero.
+fn calculate_sum_of_chebyshev_paths(galaxies: &[Point]) -> u128 {
+    let widest = galaxies.iter().map(|galaxy| galaxy.column).max().unwrap_or(0);
+    let turned: Vec<Point> = galaxies.iter()
+        .map(|galaxy| Point::new(galaxy.line + galaxy.column, galaxy.line + widest - galaxy.column))
+        .collect();
+
+    calculate_sum_of_paths_sorted(&turned) / 2
+}
+
+fn calculate_sum_of_euclidean_paths(galaxies: &[Point]) -> f64 {
+    let mut sum = 0.0;
+
+    for i in 0..galaxies.len().saturating_sub(1) {
+        for j in i + 1..galaxies.len() {
+            sum += euclidean_distance(&galaxies[i], &galaxies[j]);
         }
     }
 
     sum
 }
 
+// The `k` galaxies closest to the one at `index` under `metric`, nearest first, as
+// indices alongside their lengths. Ties go to the galaxy found first in the image.
+fn nearest_neighbours(galaxies: &[Point], index: usize, k: usize, metric: Metric) -> Vec<(usize, Length)> {
+    let mut neighbours: Vec<(usize, Length)> = galaxies.iter().enumerate()
+        .filter(|(other, _)| *other != index)
+        .map(|(other, galaxy)| (other, metric.length(&galaxies[index], galaxy)))
+        .collect();
+
+    // Lengths are never NaN, so every pair of them has an order
+    neighbours.sort_by(|(a, a_length), (b, b_length)| a_length.partial_cmp(b_length).unwrap().then(a.cmp(b)));
+    neighbours.truncate(k);
+
+    neighbours
+}
+
 fn main() {
-    let mut image = parse_image("input.txt");
-    add_galaxy_expansion(&mut image);
-    let galaxies = locate_galaxies(&image);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let flag_value = |flag: &str| -> Option<&String> {
+        args.iter().position(|arg| arg == flag).map(|index| args.get(index + 1).unwrap_or_else(|| panic!("{} needs a value", flag)))
+    };
+
+    // --factor N sets how many rows or columns each empty one grows into, and
+    // --row-factor or --column-factor override it for one direction only
+    let parse_factor = |flag: &str| -> Option<u64> {
+        flag_value(flag).map(|value| value.parse().unwrap_or_else(|_| panic!("{} needs a whole number", flag)))
+    };
+    let factor = parse_factor("--factor").unwrap_or(1_000_000);
+
+    // --metric picks how paths are measured: manhattan, chebyshev or euclidean
+    let metric = match flag_value("--metric") {
+        Some(name) => Metric::from_name(name).expect("--metric is one of manhattan, chebyshev or euclidean"),
+        None => Metric::Manhattan,
+    };
+
+    let image = parse_image("input.txt");
+    let universe = Universe::new(&image);
+    let galaxies = match (parse_factor("--row-factor"), parse_factor("--column-factor")) {
+        (None, None) => universe.expand(factor),
+        (row_factor, column_factor) => universe.expand_unevenly(row_factor.unwrap_or(factor), column_factor.unwrap_or(factor)),
+    };
+
+    match metric {
+        Metric::Manhattan => {
+            let sum = calculate_sum_of_paths_sorted(&galaxies);
+
+            println!("The sum of the paths between all pairs of galaxies is: {:?}", sum);
+        },
+        Metric::Chebyshev => println!("The sum of the Chebyshev paths between all pairs of galaxies is: {:?}", calculate_sum_of_chebyshev_paths(&galaxies)),
+        Metric::Euclidean => println!("The sum of the Euclidean paths between all pairs of galaxies is: {:.3}", calculate_sum_of_euclidean_paths(&galaxies)),
+    }
+
+    // Galaxies are numbered from 1 in reading order, as in the puzzle
+    let galaxy_number = |value: &String| -> usize {
+        match value.parse::<usize>() {
+            Ok(number) if (1..=galaxies.len()).contains(&number) => number - 1,
+            _ => panic!("There is no galaxy numbered {}", value),
+        }
+    };
+
+    // --distance A B measures the path between two galaxies
+    if let Some(index) = args.iter().position(|arg| arg == "--distance") {
+        let a = galaxy_number(args.get(index + 1).expect("--distance needs two galaxies"));
+        let b = galaxy_number(args.get(index + 2).expect("--distance needs two galaxies"));
 
-    println!("The sum of the paths between all pairs of galaxies is: {:?}", calculate_sum_of_paths(&galaxies));
+        println!("The path between galaxy {} and galaxy {} is: {}", a + 1, b + 1, metric.length(&galaxies[a], &galaxies[b]));
+    }
+
+    // --nearest A K lists the K galaxies closest to galaxy A
+    if let Some(index) = args.iter().position(|arg| arg == "--nearest") {
+        let a = galaxy_number(args.get(index + 1).expect("--nearest needs a galaxy and a count"));
+        let k: usize = args.get(index + 2).and_then(|value| value.parse().ok()).expect("--nearest needs a galaxy and a count");
+
+        for (other, length) in nearest_neighbours(&galaxies, a, k, metric) {
+            println!("Galaxy {} is {} away from galaxy {}", other + 1, length, a + 1);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -141,24 +316,12 @@ mod tests {
     }
 
     #[test]
-    fn adds_galaxy_expansion() {
-        let expected = vec![
-            "..!#.!..!.",
-            "..!..!.#!.",
-            "#.!..!..!.",
-            "--X--X--X-",
-            "..!..!#.!.",
-            ".#!..!..!.",
-            "..!..!..!#",
-            "--X--X--X-",
-            "..!..!.#!.",
-            "#.!.#!..!.",
-        ];
+    fn finds_empty_space() {
+        let universe = Universe::new(&parse_image("test.txt"));
 
-        let mut actual = parse_image("test.txt");
-        add_galaxy_expansion(&mut actual);
-
-        assert_eq!(expected, actual)
+        assert_eq!(vec![3, 7], universe.empty_rows);
+        assert_eq!(vec![2, 5, 8], universe.empty_columns);
+        assert_eq!(locate_galaxies(&parse_image("test.txt")), universe.expand(1));
     }
 
     #[test]
@@ -175,18 +338,113 @@ mod tests {
             Point::new(2_000_007, 1_000_003),
         ];
 
-        let mut actual = parse_image("test.txt");
-        add_galaxy_expansion(&mut actual);
+        let image = parse_image("test.txt");
 
-        assert_eq!(expected, locate_galaxies(&actual))
+        assert_eq!(expected, Universe::new(&image).expand(1_000_000))
     }
 
     #[test]
     fn calculates_sum_of_paths() {
-        let mut actual = parse_image("test.txt");
-        add_galaxy_expansion(&mut actual);
-        let galaxies = locate_galaxies(&actual);
+        let universe = Universe::new(&parse_image("test.txt"));
+
+        assert_eq!(82_000_210, calculate_sum_of_paths(&universe.expand(1_000_000)));
+        assert_eq!(374, calculate_sum_of_paths(&universe.expand(2)));
+        assert_eq!(1030, calculate_sum_of_paths(&universe.expand(10)));
+        assert_eq!(8410, calculate_sum_of_paths(&universe.expand(100)));
+        assert_eq!(292, calculate_sum_of_paths(&universe.expand(1)));
 
-        assert_eq!(82_000_210usize, calculate_sum_of_paths(&galaxies))
+        // Every pair crosses 82 empty lines in total, so the sum grows by 82 per step
+        assert_eq!(292 + 82 * (u64::MAX as u128 - 1), calculate_sum_of_paths(&universe.expand(u64::MAX)));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn calculates_sum_of_paths_sorted() {
+        let universe = Universe::new(&parse_image("test.txt"));
+
+        for factor in [1, 2, 10, 100, 1_000_000, u64::MAX] {
+            let galaxies = universe.expand(factor);
+
+            assert_eq!(calculate_sum_of_paths(&galaxies), calculate_sum_of_paths_sorted(&galaxies));
+        }
+
+        assert_eq!(0, calculate_sum_of_paths_sorted(&[]));
+
+        // A larger, scattered universe from a fixed seed, with some galaxies sharing
+        // lines and columns and some lying far apart
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = |bound: u64| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            u128::from(state % bound)
+        };
+        let galaxies: Vec<Point> = (0..500)
+            .map(|index| match index % 5 {
+                0 => Point::new(next(u64::MAX), next(u64::MAX)),
+                _ => Point::new(next(200), next(200)),
+            })
+            .collect();
+
+        assert_eq!(calculate_sum_of_paths(&galaxies), calculate_sum_of_paths_sorted(&galaxies));
+    }
+
+    #[test]
+    fn answers_pairwise_queries() {
+        let galaxies = Universe::new(&parse_image("test.txt")).expand(2);
+
+        assert_eq!(9, distance(&galaxies[4], &galaxies[8]));
+        assert_eq!(15, distance(&galaxies[0], &galaxies[6]));
+        assert_eq!(17, distance(&galaxies[2], &galaxies[5]));
+        assert_eq!(5, distance(&galaxies[7], &galaxies[8]));
+
+        assert_eq!(vec![(8, Length::Whole(5)), (4, Length::Whole(6)), (2, Length::Whole(9))], nearest_neighbours(&galaxies, 7, 3, Metric::Manhattan));
+        assert_eq!(8, nearest_neighbours(&galaxies, 0, 100, Metric::Manhattan).len());
+    }
+
+    #[test]
+    fn measures_other_metrics() {
+        let galaxies = vec![Point::new(0, 0), Point::new(1, 2), Point::new(3, 1)];
+
+        assert_eq!(2, chebyshev_distance(&galaxies[0], &galaxies[1]));
+        assert_eq!(7, calculate_sum_of_chebyshev_paths(&galaxies));
+        assert!((calculate_sum_of_euclidean_paths(&galaxies) - (2.0 * 5f64.sqrt() + 10f64.sqrt())).abs() < 1e-9);
+
+        // Galaxy 2 is nearer by Manhattan distance, but galaxy 1 is nearer in a straight line
+        let galaxies = vec![Point::new(0, 0), Point::new(3, 3), Point::new(0, 5)];
+
+        assert_eq!(vec![(2, Length::Whole(5)), (1, Length::Whole(6))], nearest_neighbours(&galaxies, 0, 2, Metric::Manhattan));
+        assert_eq!(vec![(1, Length::Whole(3)), (2, Length::Whole(5))], nearest_neighbours(&galaxies, 0, 2, Metric::Chebyshev));
+        assert_eq!(vec![(1, Length::Real(18f64.sqrt())), (2, Length::Real(5.0))], nearest_neighbours(&galaxies, 0, 2, Metric::Euclidean));
+        assert_eq!("4.243", Length::Real(18f64.sqrt()).to_string());
+
+        let universe = Universe::new(&parse_image("test.txt"));
+
+        for factor in [1, 2, 1_000_000, u64::MAX] {
+            let galaxies = universe.expand(factor);
+            let mut sum = 0;
+
+            for i in 0..galaxies.len() {
+                for j in i + 1..galaxies.len() {
+                    sum += chebyshev_distance(&galaxies[i], &galaxies[j]);
+                }
+            }
+
+            assert_eq!(sum, calculate_sum_of_chebyshev_paths(&galaxies));
+        }
+    }
+
+    #[test]
+    fn expands_rows_and_columns_separately() {
+        let universe = Universe::new(&parse_image("test.txt"));
+        let rows_only = universe.expand_unevenly(2, 1);
+        let columns_only = universe.expand_unevenly(1, 2);
+
+        for (index, galaxy) in rows_only.iter().enumerate() {
+            assert_eq!(universe.expand(2)[index].line, galaxy.line);
+            assert_eq!(universe.galaxies[index].column, galaxy.column);
+        }
+
+        assert_eq!(universe.expand(10), universe.expand_unevenly(10, 10));
+        assert_eq!(374, calculate_sum_of_paths(&rows_only) + calculate_sum_of_paths(&columns_only) - 292);
+    }
+}
@@ -0,0 +1,344 @@
+use std::fs;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Condition {
+    Operational,
+    Damaged,
+    Unknown,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Row {
+    pub springs: Vec<Condition>,
+    pub sequence: Vec<usize>,
+}
+
+impl Row {
+    // In part 2, we learn that each row of input is folded, and the real row is
+    // `copies` copies of it joined by unknown springs.
+    pub fn unfold(&self, copies: usize) -> Row {
+        let mut row = Row {
+            springs: Vec::with_capacity((self.springs.len() + 1) * copies),
+            sequence: Vec::with_capacity(self.sequence.len() * copies),
+        };
+
+        for copy in 0..copies {
+            if copy > 0 {
+                row.springs.push(Condition::Unknown);
+            }
+
+            row.springs.extend_from_slice(&self.springs);
+            row.sequence.extend_from_slice(&self.sequence);
+        }
+
+        row
+    }
+}
+
+pub fn parse_rows(path: &str) -> Vec<Row> {
+    let input = fs::read_to_string(path).expect("Error reading file at specified path");
+    let mut rows: Vec<Row> = Vec::new();
+
+    for line in input.lines() {
+        let mut row = Row {
+            springs: Vec::new(),
+            sequence: Vec::new(),
+        };
+
+        let (condition_records, damaged_spring_sequence) = line
+            .split_once(' ')
+            .expect("Each line should be separated by a single space.");
+
+        for record in condition_records.chars() {
+            let condition = match record {
+                '.' => Condition::Operational,
+                '#' => Condition::Damaged,
+                '?' => Condition::Unknown,
+                _ => panic!("Unexpected character present in condition_records"),
+            };
+
+            row.springs.push(condition);
+        }
+
+        for segment in damaged_spring_sequence.split(',') {
+            let segment = segment
+                .parse::<usize>()
+                .expect("Non-numeric value present in damaged_spring_sequence");
+
+            row.sequence.push(segment);
+        }
+
+        rows.push(row);
+    }
+
+    rows
+}
+
+// The number of arrangements of every tail of a row, filled in from the back. The
+// entry for (spring, group) counts the ways springs[spring..] can hold exactly the
+// groups sequence[group..], so the whole row's count is the entry for (0, 0).
+//
+// Entries saturate at u64::MAX rather than wrapping. Every entry the row's count is
+// built from is no larger than the count, so as long as the count itself fits, so do
+// the entries that matter; tails no arrangement can reach are free to saturate.
+pub struct Arrangements<'a> {
+    row: &'a Row,
+    ways: Vec<u64>,
+}
+
+impl<'a> Arrangements<'a> {
+    // None if the row has too many arrangements to count in a u64
+    pub fn new(row: &'a Row) -> Option<Self> {
+        let groups = row.sequence.len() + 1;
+
+        // One extra line past the end for groups that finish on the last spring,
+        // since a group also claims the operational spring after it.
+        let mut arrangements = Self {
+            row,
+            ways: vec![0; (row.springs.len() + 2) * groups],
+        };
+
+        for spring in [row.springs.len(), row.springs.len() + 1] {
+            arrangements.ways[spring * groups + row.sequence.len()] = 1;
+        }
+
+        for spring in (0..row.springs.len()).rev() {
+            for group in 0..groups {
+                // Either this spring is operational and the groups start later...
+                let operational = match row.springs[spring] {
+                    Condition::Damaged => 0,
+                    _ => arrangements.ways(spring + 1, group),
+                };
+
+                // ...or the next group starts right here.
+                let damaged = if arrangements.fits(spring, group) {
+                    arrangements.ways(spring + row.sequence[group] + 1, group + 1)
+                } else {
+                    0
+                };
+
+                arrangements.ways[spring * groups + group] = operational.saturating_add(damaged);
+            }
+        }
+
+        (arrangements.count() < u64::MAX).then_some(arrangements)
+    }
+
+    fn ways(&self, spring: usize, group: usize) -> u64 {
+        self.ways[spring * (self.row.sequence.len() + 1) + group]
+    }
+
+    // Whether the group at `group` can start at `spring`: none of its springs are
+    // operational, and the one after it, if any, is not damaged.
+    fn fits(&self, spring: usize, group: usize) -> bool {
+        let springs = &self.row.springs;
+
+        match self.row.sequence.get(group) {
+            Some(length) if spring + length <= springs.len() => {
+                !springs[spring..spring + length].contains(&Condition::Operational)
+                    && springs.get(spring + length) != Some(&Condition::Damaged)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.ways(0, 0)
+    }
+
+    // The arrangement at `index` among all of them, in the order `iter` yields them.
+    // Each step takes the operational branch if the index falls among the ways that
+    // branch leaves, and otherwise skips past them into the damaged branch.
+    pub fn nth(&self, mut index: u64) -> Option<String> {
+        if index >= self.count() {
+            return None;
+        }
+
+        let mut arrangement = String::with_capacity(self.row.springs.len());
+        let (mut spring, mut group) = (0, 0);
+
+        while spring < self.row.springs.len() {
+            let operational = match self.row.springs[spring] {
+                Condition::Damaged => 0,
+                _ => self.ways(spring + 1, group),
+            };
+
+            if index < operational {
+                arrangement.push('.');
+                spring += 1;
+            } else {
+                index -= operational;
+                (spring, group) = self.place_group(&mut arrangement, spring, group);
+            }
+        }
+
+        Some(arrangement)
+    }
+
+    // Writes the group starting at `spring` and the operational spring after it,
+    // returning where the arrangement carries on from.
+    fn place_group(&self, arrangement: &mut String, spring: usize, group: usize) -> (usize, usize) {
+        let length = self.row.sequence[group];
+
+        arrangement.extend(std::iter::repeat_n('#', length));
+
+        if spring + length < self.row.springs.len() {
+            arrangement.push('.');
+        }
+
+        (spring + length + 1, group + 1)
+    }
+
+    pub fn iter(&self) -> ArrangementIter<'_, 'a> {
+        let mut stack = Vec::new();
+
+        if self.count() > 0 {
+            stack.push((0, 0, String::new()));
+        }
+
+        ArrangementIter {
+            arrangements: self,
+            stack,
+        }
+    }
+
+    // A uniformly random arrangement, since every index is equally likely.
+    pub fn sample(&self, random: &mut SplitMix64) -> Option<String> {
+        match self.count() {
+            0 => None,
+            count => self.nth(random.below(count)),
+        }
+    }
+
+    // How likely each spring is to be damaged, over all arrangements. Alongside the
+    // table of ways to finish a row, this counts the ways to reach each (spring,
+    // group) from the front, so a group placed at a spring appears in the product of
+    // the two counts. Every probability is 0 if the row has no arrangements.
+    pub fn damage_probabilities(&self) -> Vec<f64> {
+        let springs = self.row.springs.len();
+        let groups = self.row.sequence.len() + 1;
+        let mut reaching: Vec<u64> = vec![0; (springs + 2) * groups];
+        let mut damaged: Vec<u64> = vec![0; springs];
+
+        reaching[0] = 1;
+
+        for spring in 0..springs {
+            for group in 0..groups {
+                let ways_here = reaching[spring * groups + group];
+
+                if ways_here == 0 {
+                    continue;
+                }
+
+                // Only step into states some arrangement passes through. Every way of
+                // reaching one of those finishes as at least one arrangement, so none
+                // of these counts can exceed `count`; dead ends can be reached more
+                // ways than that, and would overflow.
+                if self.row.springs[spring] != Condition::Damaged
+                    && self.ways(spring + 1, group) > 0
+                {
+                    reaching[(spring + 1) * groups + group] += ways_here;
+                }
+
+                if self.fits(spring, group)
+                    && self.ways(spring + self.row.sequence[group] + 1, group + 1) > 0
+                {
+                    let length = self.row.sequence[group];
+                    let next = spring + length + 1;
+
+                    reaching[next * groups + group + 1] += ways_here;
+
+                    let through = ways_here * self.ways(next, group + 1);
+
+                    for position in &mut damaged[spring..spring + length] {
+                        *position += through;
+                    }
+                }
+            }
+        }
+
+        damaged
+            .iter()
+            .map(|count| match self.count() {
+                0 => 0.0,
+                total => *count as f64 / total as f64,
+            })
+            .collect()
+    }
+}
+
+// Walks the arrangements depth first, only stepping into branches the table says
+// lead somewhere, so every step makes progress towards the next arrangement.
+pub struct ArrangementIter<'t, 'a> {
+    arrangements: &'t Arrangements<'a>,
+    stack: Vec<(usize, usize, String)>,
+}
+
+impl Iterator for ArrangementIter<'_, '_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        let arrangements = self.arrangements;
+        let springs = &arrangements.row.springs;
+
+        while let Some((spring, group, arrangement)) = self.stack.pop() {
+            if spring >= springs.len() {
+                return Some(arrangement);
+            }
+
+            // Pushed in reverse so the operational branch comes out first
+            if arrangements.fits(spring, group)
+                && arrangements.ways(spring + arrangements.row.sequence[group] + 1, group + 1) > 0
+            {
+                let mut damaged = arrangement.clone();
+                let (next_spring, next_group) =
+                    arrangements.place_group(&mut damaged, spring, group);
+
+                self.stack.push((next_spring, next_group, damaged));
+            }
+
+            if springs[spring] != Condition::Damaged && arrangements.ways(spring + 1, group) > 0 {
+                let mut operational = arrangement;
+
+                operational.push('.');
+                self.stack.push((spring + 1, group, operational));
+            }
+        }
+
+        None
+    }
+}
+
+// A small random number generator, so sampling needs no dependencies and can be
+// repeated from a seed.
+pub struct SplitMix64(pub u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+        z ^ (z >> 31)
+    }
+
+    // A number below `bound`, rejecting the values that would favour small results.
+    fn below(&mut self, bound: u64) -> u64 {
+        let zone = u64::MAX - u64::MAX % bound;
+
+        loop {
+            let value = self.next();
+
+            if value < zone {
+                return value % bound;
+            }
+        }
+    }
+}
+
+// None if the row has too many arrangements to count in a u64
+pub fn calculate_possible_arrangements(row: &Row) -> Option<u64> {
+    Arrangements::new(row).map(|arrangements| arrangements.count())
+}
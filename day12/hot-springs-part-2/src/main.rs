@@ -1,140 +1,87 @@
-use std::{collections::HashMap, fs};
+mod arrangements;
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
-enum Condition {
-    Operational,
-    Damaged,
-    Unknown,
-}
-
-#[derive(Debug, PartialEq, Eq, Hash)]
-struct Row {
-    springs: Vec<Condition>,
-    sequence: Vec<usize>,
-}
-
-fn parse_rows(path: &str) -> Vec<Row> {
-    let input = fs::read_to_string(path).expect("Error reading file at specified path");
-    let mut rows: Vec<Row> = Vec::new();
-
-    for line in input.lines() {
-        let mut row = Row {
-            springs: Vec::new(),
-            sequence: Vec::new(),
-        };
+use arrangements::*;
 
-        let (condition_records, damaged_spring_sequence) = line
-            .split_once(' ')
-            .expect("Each line should be separated by a single space.");
-
-        for record in condition_records.chars() {
-            let condition = match record {
-                '.' => Condition::Operational,
-                '#' => Condition::Damaged,
-                '?' => Condition::Unknown,
-                _ => panic!("Unexpected character present in condition_records"),
-            };
-
-            row.springs.push(condition);
-        }
-
-        for segment in damaged_spring_sequence.split(',') {
-            let segment = segment
-                .parse::<usize>()
-                .expect("Non-numeric value present in damaged_spring_sequence");
-
-            row.sequence.push(segment);
-        }
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    // --unfold N sets how many copies make up each real row, with 1 giving part 1
+    let copies: usize = match args.iter().position(|arg| arg == "--unfold") {
+        Some(index) => args
+            .get(index + 1)
+            .and_then(|value| value.parse().ok())
+            .expect("--unfold needs a whole number"),
+        None => 5,
+    };
 
-        // In part 2, we learn that for each row of input, we must push four extra copies of the row to get the
-        // real input.
-        let condition_clone = row.springs.clone();
-        let sequence_clone = row.sequence.clone();
+    let rows = parse_rows("input.txt");
+    let sum = rows.iter().try_fold(0u64, |acc, x| {
+        acc.checked_add(calculate_possible_arrangements(&x.unfold(copies))?)
+    });
 
-        for _ in 0..4 {
-            row.springs.push(Condition::Unknown);
-            row.springs.append(&mut condition_clone.clone());
-            row.sequence.append(&mut sequence_clone.clone());
+    match sum {
+        Some(sum) => println!(
+            "The sum of possible arrangements of broken equipment is: {:?}",
+            sum
+        ),
+        None => {
+            eprintln!(
+                "The arrangements of rows unfolded {} times are too many to count in a u64",
+                copies
+            );
+            std::process::exit(1);
         }
-
-        rows.push(row);
     }
 
-    rows
-}
-
-fn calculate_possible_arrangements(row: &Row, cache: &mut HashMap<Row, u64>) -> u64 {
-    // Our strategy will be to recurse through valid combinations and break out early
-    // if there is a conflict between the combination and the Row's sequence.
-
-    // Start the recursion
-    recurse(row.springs.clone(), row.sequence.clone(), cache)
-}
-
-// Recursion simplification and caching inspiration drawn from HyperNeutrino's Python solution on YouTube
-// Our original implementation...was not very performant
-fn recurse(springs: Vec<Condition>, sequence: Vec<usize>, cache: &mut HashMap<Row, u64>) -> u64 {
-    // Memoize results
-    let row = Row {
-        springs: springs.clone(),
-        sequence: sequence.clone(),
-    };
-
-    if let Some(_) = cache.get(&row) {
-        return *cache.get(&row).unwrap();
-    }
-
-    // Base cases
-    if springs.len() == 0 {
-        return match sequence {
-            s if s.len() == 0 => 1,
-            _ => 0,
-        };
-    } else if sequence.len() == 0 {
-        return match springs {
-            s if s.contains(&Condition::Damaged) => 0,
-            _ => 1,
+    // --inspect N looks into the row on line N: its first arrangements (as many as
+    // --limit, 10 by default), a random one (repeatable with --seed) and how likely
+    // each unknown spring is to be damaged
+    if let Some(index) = args.iter().position(|arg| arg == "--inspect") {
+        let flag_value = |flag: &str| -> Option<u64> {
+            let index = args.iter().position(|arg| arg == flag)?;
+
+            Some(
+                args.get(index + 1)
+                    .and_then(|value| value.parse().ok())
+                    .unwrap_or_else(|| panic!("{} needs a whole number", flag)),
+            )
         };
-    }
+        let line: usize = args
+            .get(index + 1)
+            .and_then(|value| value.parse().ok())
+            .filter(|line| (1..=rows.len()).contains(line))
+            .expect("--inspect needs the line number of a row");
+        let limit = flag_value("--limit").unwrap_or(10) as usize;
+        let seed = flag_value("--seed").unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+        });
 
-    let mut result = 0;
+        let row = rows[line - 1].unfold(copies);
+        let arrangements = Arrangements::new(&row)
+            .unwrap_or_else(|| panic!("Line {} has too many arrangements to count in a u64", line));
 
-    if springs[0] != Condition::Damaged {
-        result += recurse(springs[1..].to_vec(), sequence.clone(), cache);
-    }
+        println!("Line {} has {} arrangements:", line, arrangements.count());
 
-    if springs[0] != Condition::Operational {
-        if sequence[0] <= springs.len()
-            && !springs[..sequence[0]]
-                .iter()
-                .any(|x| *x == Condition::Operational)
-            && (sequence[0] == springs.len() || springs[sequence[0]] != Condition::Damaged)
-        {
-            let sliced_springs = match sequence[0] {
-                x if x == springs.len() => Vec::new(),
-                x => springs[x + 1..].to_vec(),
-            };
-
-            result += recurse(sliced_springs, sequence[1..].to_vec(), cache)
+        for arrangement in arrangements.iter().take(limit) {
+            println!("  {}", arrangement);
         }
-    }
-
-    cache.insert(row, result);
 
-    result
-}
-
-fn main() {
-    let mut cache: HashMap<Row, u64> = HashMap::new();
-    let rows = parse_rows("input.txt");
-    let sum = rows.iter().fold(0u64, |acc, x| {
-        acc + calculate_possible_arrangements(x, &mut cache)
-    });
+        if let Some(arrangement) = arrangements.sample(&mut SplitMix64(seed)) {
+            println!("Picked at random with seed {}: {}", seed, arrangement);
+        }
 
-    println!(
-        "The sum of possible arrangements of broken equipment is: {:?}",
-        sum
-    );
+        for (position, probability) in arrangements.damage_probabilities().iter().enumerate() {
+            if row.springs[position] == Condition::Unknown {
+                println!(
+                    "  Spring {} is damaged with probability {:.4}",
+                    position + 1,
+                    probability
+                );
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -262,12 +209,207 @@ mod tests {
     #[test]
     fn calculates_possible_arrangements() {
         let rows = parse_rows("test.txt");
-        let mut cache: HashMap<Row, u64> = HashMap::new();
 
         let actual = rows.iter().fold(0u64, |acc, x| {
-            acc + calculate_possible_arrangements(x, &mut cache)
+            acc + calculate_possible_arrangements(&x.unfold(5)).unwrap()
         });
 
         assert_eq!(525152, actual)
     }
+
+    #[test]
+    fn calculates_arrangements_per_row() {
+        let rows = parse_rows("test.txt");
+        let folded: Vec<u64> = rows
+            .iter()
+            .filter_map(calculate_possible_arrangements)
+            .collect();
+        let unfolded: Vec<u64> = rows
+            .iter()
+            .filter_map(|row| calculate_possible_arrangements(&row.unfold(5)))
+            .collect();
+
+        assert_eq!(vec![1, 4, 1, 1, 4, 10], folded);
+        assert_eq!(vec![1, 16384, 1, 16, 2500, 506250], unfolded);
+        assert_eq!(Some(1), calculate_possible_arrangements(&rows[0].unfold(0)));
+    }
+
+    #[test]
+    fn reports_arrangements_too_many_to_count() {
+        let rows = parse_rows("test.txt");
+
+        // Each copy after the first multiplies the last row's 10 arrangements by 15
+        assert_eq!(
+            Some(10 * 15u64.pow(15)),
+            calculate_possible_arrangements(&rows[5].unfold(16))
+        );
+        assert_eq!(None, calculate_possible_arrangements(&rows[5].unfold(17)));
+    }
+
+    #[test]
+    fn unfolds_rows() {
+        let row = Row {
+            springs: vec![Condition::Operational, Condition::Damaged],
+            sequence: vec![1],
+        };
+        let expected = Row {
+            springs: vec![
+                Condition::Operational,
+                Condition::Damaged,
+                Condition::Unknown,
+                Condition::Operational,
+                Condition::Damaged,
+            ],
+            sequence: vec![1, 1],
+        };
+
+        assert_eq!(expected, row.unfold(2));
+        assert_eq!(row, row.unfold(1));
+    }
+
+    // Every way of filling in the unknown springs that matches the sequence
+    fn brute_force(row: &Row) -> Vec<String> {
+        let unknown = row
+            .springs
+            .iter()
+            .filter(|spring| **spring == Condition::Unknown)
+            .count();
+        let mut found = Vec::new();
+
+        for mask in 0..1u32 << unknown {
+            let mut bit = 0;
+            let arrangement: String = row
+                .springs
+                .iter()
+                .map(|spring| match spring {
+                    Condition::Operational => '.',
+                    Condition::Damaged => '#',
+                    Condition::Unknown => {
+                        bit += 1;
+
+                        if mask & (1 << (bit - 1)) == 0 {
+                            '.'
+                        } else {
+                            '#'
+                        }
+                    }
+                })
+                .collect();
+            let groups: Vec<usize> = arrangement
+                .split('.')
+                .filter(|group| !group.is_empty())
+                .map(|group| group.len())
+                .collect();
+
+            if groups == row.sequence {
+                found.push(arrangement);
+            }
+        }
+
+        found.sort();
+        found
+    }
+
+    #[test]
+    fn enumerates_arrangements() {
+        for row in parse_rows("test.txt") {
+            let arrangements = Arrangements::new(&row).unwrap();
+            let listed: Vec<String> = arrangements.iter().collect();
+            let mut sorted = listed.clone();
+
+            sorted.sort();
+
+            assert_eq!(brute_force(&row), sorted);
+            assert_eq!(
+                listed,
+                (0..arrangements.count())
+                    .filter_map(|index| arrangements.nth(index))
+                    .collect::<Vec<String>>()
+            );
+            assert_eq!(None, arrangements.nth(arrangements.count()));
+        }
+
+        // Unfolded, the row has too many arrangements to brute force, but the last
+        // few from the iterator can still be pinned against nth
+        let rows = parse_rows("test.txt");
+        let unfolded = rows[5].unfold(2);
+        let arrangements = Arrangements::new(&unfolded).unwrap();
+        let listed: Vec<String> = arrangements.iter().collect();
+
+        assert_eq!(150, listed.len());
+        assert_eq!(
+            listed[147..],
+            (147..150)
+                .filter_map(|index| arrangements.nth(index))
+                .collect::<Vec<String>>()
+        );
+    }
+
+    #[test]
+    fn samples_arrangements() {
+        let rows = parse_rows("test.txt");
+        let arrangements = Arrangements::new(&rows[5]).unwrap();
+        let valid = brute_force(&rows[5]);
+        let mut random = SplitMix64(2023);
+        let mut seen = vec![0; valid.len()];
+
+        for _ in 0..1000 {
+            let sample = arrangements.sample(&mut random).unwrap();
+            let index = valid.iter().position(|arrangement| *arrangement == sample);
+
+            seen[index.expect("Samples should be valid arrangements")] += 1;
+        }
+
+        // Each of the 10 arrangements should turn up around 100 times
+        assert!(seen.iter().all(|count| (50..150).contains(count)));
+
+        let impossible = Row {
+            springs: vec![Condition::Damaged],
+            sequence: vec![2],
+        };
+
+        assert_eq!(
+            None,
+            Arrangements::new(&impossible).unwrap().sample(&mut random)
+        );
+    }
+
+    #[test]
+    fn calculates_damage_probabilities() {
+        let rows = parse_rows("test.txt");
+        let expected = vec![
+            0.0, 0.5, 0.5, 0.0, 0.0, 0.5, 0.5, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 0.0,
+        ];
+
+        assert_eq!(
+            expected,
+            Arrangements::new(&rows[1]).unwrap().damage_probabilities()
+        );
+
+        for row in &rows {
+            let valid = brute_force(row);
+            let probabilities = Arrangements::new(row).unwrap().damage_probabilities();
+
+            for (position, probability) in probabilities.iter().enumerate() {
+                let damaged = valid
+                    .iter()
+                    .filter(|arrangement| arrangement.as_bytes()[position] == b'#')
+                    .count();
+
+                assert!((probability - damaged as f64 / valid.len() as f64).abs() < 1e-12);
+            }
+        }
+
+        // 100 groups only fit in 199 springs one way, but far more ways of placing the
+        // first groups run out of room than a u64 can count
+        let tight = Row {
+            springs: vec![Condition::Unknown; 199],
+            sequence: vec![1; 100],
+        };
+        let probabilities = Arrangements::new(&tight).unwrap().damage_probabilities();
+
+        for (position, probability) in probabilities.iter().enumerate() {
+            assert_eq!(if position % 2 == 0 { 1.0 } else { 0.0 }, *probability);
+        }
+    }
 }
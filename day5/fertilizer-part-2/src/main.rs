@@ -19,112 +19,328 @@ impl RangeMap {
     }
 }
 
-fn parse_seeds(s: &str) -> Vec<RangeMap> {
-    let mut values: Vec<u64> = Vec::new();
-    let mut seeds: Vec<RangeMap> = Vec::new();
-
-    match s.split_once(": ") {
-        Some((a, b)) if a == "seeds" => {
-            for value in b.split_whitespace() {
-                values.push(
-                    value
-                        .parse::<u64>()
-                        .expect("Seed values should all be numbers."),
-                )
-            }
+// A problem found while validating an almanac. Line numbers are 1-based and
+// refer back to the almanac text the diagnostic was produced from.
+#[derive(Debug, PartialEq, Eq)]
+enum Diagnostic {
+    // A line that could not be read as a seed list or a map entry
+    Malformed {
+        line: usize,
+        reason: String,
+    },
+    // source + range or destination + range does not fit in a u64
+    Overflow {
+        map: String,
+        line: usize,
+    },
+    // Two entries in the same map claim some of the same source values
+    Overlap {
+        map: String,
+        line: usize,
+        other_line: usize,
+        start: u64,
+        end: u64,
+    },
+    // Source values between two entries, which fall through unmapped
+    Gap {
+        map: String,
+        after_line: usize,
+        before_line: usize,
+        start: u64,
+        end: u64,
+    },
+    // Source values below a map's first entry, which fall through unmapped
+    LeadingGap {
+        map: String,
+        line: usize,
+        end: u64,
+    },
+    // Source values above a map's last entry, which fall through unmapped
+    TrailingGap {
+        map: String,
+        line: usize,
+        start: u64,
+    },
+    // Part of a seed range that no entry in the first map covers
+    UncoveredSeeds {
+        line: usize,
+        start: u64,
+        end: u64,
+    },
+}
 
-            let mut previous_value = 0;
-
-            for (index, value) in values.into_iter().enumerate() {
-                match index {
-                    i if i % 2 == 0 => previous_value = value,
-                    _ => {
-                        seeds.push(RangeMap::new((
-                            value + previous_value,
-                            previous_value,
-                            value,
-                        )));
-                    }
-                }
+impl Diagnostic {
+    // Gaps and uncovered seeds are legal (values map to themselves), so only
+    // the remaining diagnostics should stop a run
+    fn is_error(&self) -> bool {
+        !matches!(
+            self,
+            Diagnostic::Gap { .. }
+                | Diagnostic::LeadingGap { .. }
+                | Diagnostic::TrailingGap { .. }
+                | Diagnostic::UncoveredSeeds { .. }
+        )
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Diagnostic::Malformed { line, reason } => {
+                write!(f, "error: line {}: {}", line, reason)
             }
+            Diagnostic::Overflow { map, line } => {
+                write!(f, "error: line {}: {} entry overflows a u64", line, map)
+            }
+            Diagnostic::Overlap {
+                map,
+                line,
+                other_line,
+                start,
+                end,
+            } => write!(
+                f,
+                "error: line {}: {} entry overlaps line {} on source values {}..{}",
+                line, map, other_line, start, end
+            ),
+            Diagnostic::Gap {
+                map,
+                after_line,
+                before_line,
+                start,
+                end,
+            } => write!(
+                f,
+                "warning: lines {} and {}: {} leaves source values {}..{} unmapped",
+                after_line, before_line, map, start, end
+            ),
+            Diagnostic::LeadingGap { map, line, end } => write!(
+                f,
+                "warning: line {}: {} leaves source values 0..{} unmapped",
+                line, map, end
+            ),
+            Diagnostic::TrailingGap { map, line, start } => write!(
+                f,
+                "warning: line {}: {} leaves source values {}.. unmapped",
+                line, map, start
+            ),
+            Diagnostic::UncoveredSeeds { line, start, end } => write!(
+                f,
+                "warning: line {}: seeds {}..{} are outside the first map's domain",
+                line, start, end
+            ),
+        }
+    }
+}
+
+// Returns the parts of start..end which are not inside any of the given
+// sorted, non-overlapping ranges
+fn uncovered_parts(start: u64, end: u64, covered: &[(u64, u64)]) -> Vec<(u64, u64)> {
+    let mut parts = Vec::new();
+    let mut cursor = start;
 
-            seeds
+    for &(a, b) in covered {
+        if b <= cursor {
+            continue;
+        }
+        if a >= end {
+            break;
         }
-        _ => panic!("Not a valid seed string"),
+        if a > cursor {
+            parts.push((cursor, a));
+        }
+        cursor = b;
+    }
+
+    if cursor < end {
+        parts.push((cursor, end));
     }
+
+    parts
 }
 
-fn parse_maps(s: &str) -> Vec<Vec<RangeMap>> {
-    // Create maps
-    let seeds_soil_map: Vec<RangeMap> = Vec::new();
-    let soil_fertilizer_map: Vec<RangeMap> = Vec::new();
-    let fertilizer_water_map: Vec<RangeMap> = Vec::new();
-    let water_light_map: Vec<RangeMap> = Vec::new();
-    let light_temperature_map: Vec<RangeMap> = Vec::new();
-    let temperature_humidity_map: Vec<RangeMap> = Vec::new();
-    let humidity_location_map: Vec<RangeMap> = Vec::new();
-
-    // Aggregate maps
-    let mut maps = vec![
-        seeds_soil_map,
-        soil_fertilizer_map,
-        fertilizer_water_map,
-        water_light_map,
-        light_temperature_map,
-        temperature_humidity_map,
-        humidity_location_map,
-    ];
-
-    let mut map_iterator = 0;
-
-    for line in s.trim().lines() {
-        match line {
-            // On an empty line, move to the next map
-            l if l.len() == 0 => map_iterator += 1,
-            // On a line beginning with a number, process the line into the current map
-            l if l.starts_with(|c: char| c.is_ascii_digit()) => {
-                let values = l.split(" ");
-
-                let values: Vec<u64> = values.map(|val| val.parse::<u64>().unwrap()).collect();
-
-                maps[map_iterator].push(RangeMap::new((values[0], values[1], values[2])));
+// An almanac along with the line each of its values was read from, so that
+// diagnostics can point back at the text
+#[derive(Debug, PartialEq, Eq)]
+struct Almanac {
+    // Seed ranges as (line, start, end)
+    seeds: Vec<(usize, u64, u64)>,
+    // Each map's name along with its entries and the lines they came from,
+    // sorted by source
+    sections: Vec<(String, Vec<(usize, RangeMap)>)>,
+}
+
+// Reads an almanac, or returns a diagnostic for every line that couldn't be read
+fn parse_almanac(input: &str) -> Result<Almanac, Vec<Diagnostic>> {
+    let mut diagnostics = Vec::new();
+    let mut seeds: Vec<(usize, u64, u64)> = Vec::new();
+    let mut sections: Vec<(String, Vec<(usize, RangeMap)>)> = Vec::new();
+
+    for (index, line) in input.lines().enumerate() {
+        let line_number = index + 1;
+
+        match line.trim() {
+            "" => continue,
+            l if l.starts_with("seeds:") => {
+                let values: Result<Vec<u64>, _> = l["seeds:".len()..]
+                    .split_whitespace()
+                    .map(|value| value.parse::<u64>())
+                    .collect();
+
+                match values {
+                    Ok(values) if values.len() % 2 == 0 => {
+                        for pair in values.chunks(2) {
+                            match pair[0].checked_add(pair[1]) {
+                                Some(end) => seeds.push((line_number, pair[0], end)),
+                                None => diagnostics.push(Diagnostic::Overflow {
+                                    map: String::from("seeds"),
+                                    line: line_number,
+                                }),
+                            }
+                        }
+                    }
+                    Ok(_) => diagnostics.push(Diagnostic::Malformed {
+                        line: line_number,
+                        reason: String::from("seed values should come in start/length pairs"),
+                    }),
+                    Err(_) => diagnostics.push(Diagnostic::Malformed {
+                        line: line_number,
+                        reason: String::from("seed values should all be numbers"),
+                    }),
+                }
+            }
+            l if l.ends_with("map:") => {
+                sections.push((l.trim_end_matches("map:").trim().to_string(), Vec::new()))
+            }
+            l => {
+                let values: Result<Vec<u64>, _> = l
+                    .split_whitespace()
+                    .map(|value| value.parse::<u64>())
+                    .collect();
+
+                match (values, sections.last_mut()) {
+                    (_, None) => diagnostics.push(Diagnostic::Malformed {
+                        line: line_number,
+                        reason: String::from("entry appears before any map header"),
+                    }),
+                    (Ok(values), Some((_, entries))) if values.len() == 3 => entries.push((
+                        line_number,
+                        RangeMap::new((values[0], values[1], values[2])),
+                    )),
+                    _ => diagnostics.push(Diagnostic::Malformed {
+                        line: line_number,
+                        reason: String::from("map entries should be three numbers"),
+                    }),
+                }
             }
-            _ => continue,
         }
     }
 
-    maps
+    if !diagnostics.is_empty() {
+        return Err(diagnostics);
+    }
+
+    for (_, entries) in &mut sections {
+        entries.sort_by_key(|(_, range_map)| range_map.source);
+    }
+
+    Ok(Almanac { seeds, sections })
 }
 
-fn find_lowest_location_value(file: &str) -> u64 {
-    // Read input
-    let input = fs::read_to_string(file).expect("File not found.");
-    let seeds;
-    let mut maps;
-
-    // Get the seeds and maps
-    match input.split_once('\n') {
-        Some((a, b)) => {
-            seeds = parse_seeds(a);
-            maps = parse_maps(b);
+fn validate_almanac(almanac: &Almanac) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    // Ranges covered by the first map, merged, for checking the seeds against
+    let mut first_map_domain: Vec<(u64, u64)> = Vec::new();
+
+    for (section_index, (name, entries)) in almanac.sections.iter().enumerate() {
+        // The line and end of the furthest-reaching entry seen so far
+        let mut furthest: Option<(usize, u64)> = None;
+
+        for (line, range_map) in entries.iter() {
+            let end = match (
+                range_map.source.checked_add(range_map.range),
+                range_map.destination.checked_add(range_map.range),
+            ) {
+                (Some(end), Some(_)) => end,
+                _ => {
+                    diagnostics.push(Diagnostic::Overflow {
+                        map: name.clone(),
+                        line: *line,
+                    });
+                    continue;
+                }
+            };
+
+            if range_map.range == 0 {
+                continue;
+            }
+
+            match furthest {
+                None if range_map.source > 0 => diagnostics.push(Diagnostic::LeadingGap {
+                    map: name.clone(),
+                    line: *line,
+                    end: range_map.source,
+                }),
+                Some((other_line, furthest_end)) if range_map.source < furthest_end => diagnostics
+                    .push(Diagnostic::Overlap {
+                        map: name.clone(),
+                        line: *line,
+                        other_line,
+                        start: range_map.source,
+                        end: std::cmp::min(end, furthest_end),
+                    }),
+                Some((other_line, furthest_end)) if range_map.source > furthest_end => diagnostics
+                    .push(Diagnostic::Gap {
+                        map: name.clone(),
+                        after_line: other_line,
+                        before_line: *line,
+                        start: furthest_end,
+                        end: range_map.source,
+                    }),
+                _ => (),
+            }
+
+            if section_index == 0 {
+                match first_map_domain.last_mut() {
+                    Some((_, last_end)) if range_map.source <= *last_end => {
+                        *last_end = std::cmp::max(*last_end, end)
+                    }
+                    _ => first_map_domain.push((range_map.source, end)),
+                }
+            }
+
+            if furthest.is_none_or(|(_, furthest_end)| end > furthest_end) {
+                furthest = Some((*line, end));
+            }
+        }
+
+        if let Some((line, end)) = furthest.filter(|&(_, end)| end < u64::MAX) {
+            diagnostics.push(Diagnostic::TrailingGap {
+                map: name.clone(),
+                line,
+                start: end,
+            });
         }
-        _ => panic!(
-            "Input was not formatted as expected - please check file contents and try again."
-        ),
     }
 
-    // Sort the maps by the source input to speed up the search process
-    for map in &mut maps {
-        map.sort()
+    for &(line, start, end) in &almanac.seeds {
+        for (start, end) in uncovered_parts(start, end, &first_map_domain) {
+            diagnostics.push(Diagnostic::UncoveredSeeds { line, start, end });
+        }
     }
 
-    let mut minimum_location_value = std::u64::MAX;
+    diagnostics
+}
+
+// Solves an almanac that has already been read and validated
+fn find_lowest_location(almanac: &Almanac) -> u64 {
+    let mut minimum_location_value = u64::MAX;
 
-    for seed in seeds {
-        for seed in seed.source..seed.destination {
+    for &(_, start, end) in &almanac.seeds {
+        for seed in start..end {
             let mut value = seed;
-            for map in &maps {
-                'current_range_map_loop: for range_map in map {
+            for (_, map) in &almanac.sections {
+                'current_range_map_loop: for (_, range_map) in map {
                     // Since the RangeMaps are sorted, we can break out early if we
                     // a) find a match for our target range, or
                     // b) find a source greater than our value
@@ -146,9 +362,32 @@ fn find_lowest_location_value(file: &str) -> u64 {
 }
 
 fn main() {
+    // Refuse to run on an almanac with ambiguous or overflowing entries; pass
+    // --validate to also see the warnings
+    let input = fs::read_to_string("input.txt").expect("File not found.");
+    let show_warnings = std::env::args().any(|arg| arg == "--validate");
+    let almanac = parse_almanac(&input).unwrap_or_else(|diagnostics| {
+        for diagnostic in &diagnostics {
+            eprintln!("{}", diagnostic);
+        }
+
+        std::process::exit(1);
+    });
+    let diagnostics = validate_almanac(&almanac);
+
+    for diagnostic in &diagnostics {
+        if show_warnings || diagnostic.is_error() {
+            eprintln!("{}", diagnostic);
+        }
+    }
+
+    if diagnostics.iter().any(Diagnostic::is_error) {
+        std::process::exit(1);
+    }
+
     println!(
         "The lowest location value is {:?}",
-        find_lowest_location_value("input.txt")
+        find_lowest_location(&almanac)
     );
 }
 
@@ -158,48 +397,194 @@ mod tests {
 
     #[test]
     fn parses_seeds_as_expected() {
-        let expected: Vec<RangeMap> =
-            vec![RangeMap::new((93, 79, 14)), RangeMap::new((68, 55, 13))];
-
         let test_input = fs::read_to_string("test.txt").unwrap();
+        let almanac = parse_almanac(&test_input).unwrap();
 
-        let seeds = test_input.lines().next().unwrap();
-
-        assert_eq!(expected, parse_seeds(seeds))
+        assert_eq!(vec![(1, 79, 93), (1, 55, 68)], almanac.seeds)
     }
 
     #[test]
     fn parses_maps_as_expected() {
+        // Entries are sorted by source, so they no longer appear in file order
         let expected = vec![
-            vec![RangeMap::new((50, 98, 2)), RangeMap::new((52, 50, 48))],
             vec![
-                RangeMap::new((0, 15, 37)),
-                RangeMap::new((37, 52, 2)),
-                RangeMap::new((39, 0, 15)),
+                (5, RangeMap::new((52, 50, 48))),
+                (4, RangeMap::new((50, 98, 2))),
+            ],
+            vec![
+                (10, RangeMap::new((39, 0, 15))),
+                (8, RangeMap::new((0, 15, 37))),
+                (9, RangeMap::new((37, 52, 2))),
+            ],
+            vec![
+                (15, RangeMap::new((42, 0, 7))),
+                (16, RangeMap::new((57, 7, 4))),
+                (14, RangeMap::new((0, 11, 42))),
+                (13, RangeMap::new((49, 53, 8))),
+            ],
+            vec![
+                (19, RangeMap::new((88, 18, 7))),
+                (20, RangeMap::new((18, 25, 70))),
             ],
             vec![
-                RangeMap::new((49, 53, 8)),
-                RangeMap::new((0, 11, 42)),
-                RangeMap::new((42, 0, 7)),
-                RangeMap::new((57, 7, 4)),
+                (24, RangeMap::new((81, 45, 19))),
+                (25, RangeMap::new((68, 64, 13))),
+                (23, RangeMap::new((45, 77, 23))),
             ],
-            vec![RangeMap::new((88, 18, 7)), RangeMap::new((18, 25, 70))],
             vec![
-                RangeMap::new((45, 77, 23)),
-                RangeMap::new((81, 45, 19)),
-                RangeMap::new((68, 64, 13)),
+                (29, RangeMap::new((1, 0, 69))),
+                (28, RangeMap::new((0, 69, 1))),
+            ],
+            vec![
+                (32, RangeMap::new((60, 56, 37))),
+                (33, RangeMap::new((56, 93, 4))),
             ],
-            vec![RangeMap::new((0, 69, 1)), RangeMap::new((1, 0, 69))],
-            vec![RangeMap::new((60, 56, 37)), RangeMap::new((56, 93, 4))],
         ];
         let test_input = fs::read_to_string("test.txt").unwrap();
-        let (_, lines) = test_input.split_once('\n').unwrap();
+        let almanac = parse_almanac(&test_input).unwrap();
+        let names: Vec<&str> = almanac
+            .sections
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
 
-        assert_eq!(expected, parse_maps(lines))
+        assert_eq!("seed-to-soil", names[0]);
+        assert_eq!("humidity-to-location", names[6]);
+        assert_eq!(
+            expected,
+            almanac
+                .sections
+                .into_iter()
+                .map(|(_, entries)| entries)
+                .collect::<Vec<_>>()
+        )
     }
 
     #[test]
     fn lowest_value_returned() {
-        assert_eq!(46, find_lowest_location_value("test.txt"))
+        let test_input = fs::read_to_string("test.txt").unwrap();
+
+        assert_eq!(
+            46,
+            find_lowest_location(&parse_almanac(&test_input).unwrap())
+        )
+    }
+
+    #[test]
+    fn validates_clean_almanac() {
+        let test_input = fs::read_to_string("test.txt").unwrap();
+
+        let almanac = parse_almanac(&test_input).unwrap();
+        let diagnostics = validate_almanac(&almanac);
+
+        // Every map in the example leaves values on either side of it unmapped
+        assert!(!diagnostics.is_empty());
+        assert!(!diagnostics.iter().any(Diagnostic::is_error))
+    }
+
+    #[test]
+    fn reports_almanac_problems() {
+        let almanac = "seeds: 5 20
+
+seed-to-soil map:
+0 0 10
+50 8 4
+0 15 5
+
+soil-to-fertilizer map:
+0 18446744073709551615 2
+";
+        let expected = vec![
+            Diagnostic::Overlap {
+                map: String::from("seed-to-soil"),
+                line: 5,
+                other_line: 4,
+                start: 8,
+                end: 10,
+            },
+            Diagnostic::Gap {
+                map: String::from("seed-to-soil"),
+                after_line: 5,
+                before_line: 6,
+                start: 12,
+                end: 15,
+            },
+            Diagnostic::TrailingGap {
+                map: String::from("seed-to-soil"),
+                line: 6,
+                start: 20,
+            },
+            Diagnostic::Overflow {
+                map: String::from("soil-to-fertilizer"),
+                line: 9,
+            },
+            Diagnostic::UncoveredSeeds {
+                line: 1,
+                start: 12,
+                end: 15,
+            },
+            Diagnostic::UncoveredSeeds {
+                line: 1,
+                start: 20,
+                end: 25,
+            },
+        ];
+
+        assert_eq!(expected, validate_almanac(&parse_almanac(almanac).unwrap()))
+    }
+
+    #[test]
+    fn reports_gap_before_first_range() {
+        let almanac = "seeds: 10 5
+
+seed-to-soil map:
+0 10 5
+5 15 18446744073709551600
+";
+        let expected = vec![Diagnostic::LeadingGap {
+            map: String::from("seed-to-soil"),
+            line: 4,
+            end: 10,
+        }];
+
+        assert_eq!(expected, validate_almanac(&parse_almanac(almanac).unwrap()))
+    }
+
+    #[test]
+    fn reports_gap_after_last_range() {
+        let almanac = "seeds: 0 5
+
+seed-to-soil map:
+20 0 10
+0 10 20
+";
+        let expected = vec![Diagnostic::TrailingGap {
+            map: String::from("seed-to-soil"),
+            line: 5,
+            start: 30,
+        }];
+
+        assert_eq!(expected, validate_almanac(&parse_almanac(almanac).unwrap()))
+    }
+
+    #[test]
+    fn rejects_unreadable_lines() {
+        let almanac = "seeds: 1 2 3
+
+seed-to-soil map:
+0 10
+";
+        let expected = vec![
+            Diagnostic::Malformed {
+                line: 1,
+                reason: String::from("seed values should come in start/length pairs"),
+            },
+            Diagnostic::Malformed {
+                line: 4,
+                reason: String::from("map entries should be three numbers"),
+            },
+        ];
+
+        assert_eq!(Err(expected), parse_almanac(almanac))
     }
 }
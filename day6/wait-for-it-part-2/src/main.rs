@@ -1,80 +1,180 @@
 use std::fs;
 
+use num_bigint::BigUint;
+
+// Races default to u64 values, which covers the puzzle input; Race<BigUint> holds
+// kerned numbers of any length.
 #[derive(Debug, PartialEq, Eq)]
-struct Race {
-    time: u64,
-    distance: u64
+struct Race<T = u64> {
+    time: T,
+    distance: T
 }
 
-impl Race {
+impl<T: Default> Race<T> {
     fn empty() -> Self {
         Self {
-            time: 0,
-            distance: 0
+            time: T::default(),
+            distance: T::default()
         }
     }
 }
 
-fn parse_races(file_path: &str) -> Vec<Race> {
+// Reads the time and distance lines, ignoring the kerning between their digits
+fn read_kerned_values(file_path: &str) -> (String, String) {
     let input = fs::read_to_string(file_path).expect("Error parsing the file at the specified path");
 
-    let mut races: Vec<Race> = Vec::new();
-
     match input.split_once('\n') {
         Some((a, b)) if a.starts_with("Time:") && b.starts_with("Distance:") => {
             // Reduce the slices to just the data
-            let a = a.split_once(':').unwrap().1.trim().replace(" ", "");
-            let value = a.parse::<u64>().expect("There was an error parsing the value from the input");
-            let mut race = Race::empty();
-            race.time = value;
-
-            let b = b.split_once(':').unwrap().1.trim().replace(" ", "");
-            race.distance = b.parse::<u64>().unwrap();
-            
-            races.push(race);
+            let a: String = a.split_once(':').unwrap().1.split_whitespace().collect();
+            let b: String = b.split_once(':').unwrap().1.split_whitespace().collect();
+
+            (a, b)
         },
         _ => panic!("File did not match expected format. Please verify file contents and try again."),
     }
+}
 
-    races
+fn parse_big_races(file_path: &str) -> Vec<Race<BigUint>> {
+    let (a, b) = read_kerned_values(file_path);
+
+    let mut race = Race::empty();
+    race.time = a.parse::<BigUint>().expect("There was an error parsing the value from the input");
+    race.distance = b.parse::<BigUint>().unwrap();
+
+    vec![race]
+}
+
+// Whether holding the button for `hold` ms beats the record distance. The product
+// can only overflow a u128 when it is far beyond any u128 record, so overflow wins.
+fn beats_record(hold: u128, time: u128, distance: u128) -> bool {
+    match hold.checked_mul(time - hold) {
+        Some(travelled) => travelled > distance,
+        None => true,
+    }
 }
 
-fn quantify_winning_strategies(races: &Vec<Race>) -> Vec<u64> {
-    // The furthest distance achievable in any race is achieved at race.time / 2.
-    // The minimum winning distance achievable is at the value of x closest to 0 which satisfies
-    //     x * (race.time - x) > race.distance
-    // The total set of winning_strategies lies within the set of integers y that are bound by x < y < race.time - x
-    // This quantity is equal to ((race.time / 2) - x + 1) * 2  where race.time is an odd number, and
-    //     ((race.time / 2) - x + 1) * 2 - 1 where race.time is an even number
-    
-    let mut winning_strategies: Vec<u64> = Vec::new();
-
-    for race in races {
-        'current_race: for  x in 1..race.time / 2 {
-            if x * (race.time - x) > race.distance {
-                let quantity = match race.time {
-                    t if t % 2 != 0 => ((race.time / 2) - x + 1) * 2,
-                    _ => ((race.time / 2) - x + 1) * 2 - 1
-                };
-
-                winning_strategies.push(quantity);
-
-                break 'current_race;
+fn count_winning_strategies(time: u128, distance: u128) -> u128 {
+    // The distance travelled, x * (time - x), peaks at x = time / 2 and is symmetric
+    // about it, so a race that can't be won there can't be won at all.
+    let peak = time / 2;
+
+    if !beats_record(peak, time, distance) {
+        return 0;
+    }
+
+    // The first winning hold is just above the smaller root of x^2 - time * x + distance,
+    //     (time - sqrt(time^2 - 4 * distance)) / 2
+    // While time^2 fits in a u128 the discriminant is exact, and its integer square
+    // root leaves the estimate within a step of the first winning hold.
+    let first_winner = match time.checked_mul(time) {
+        Some(square) => {
+            let mut first_winner = (time - (square - 4 * distance).isqrt()) / 2;
+
+            while first_winner > 0 && beats_record(first_winner - 1, time, distance) {
+                first_winner -= 1;
+            }
+
+            while !beats_record(first_winner, time, distance) {
+                first_winner += 1;
+            }
+
+            first_winner
+        }
+        // Beyond that, bisect between holding for 0 ms, which never wins, and holding
+        // for `peak` ms, which does.
+        None => {
+            let (mut losing, mut winning) = (0, peak);
+
+            while winning - losing > 1 {
+                let middle = losing + (winning - losing) / 2;
+
+                if beats_record(middle, time, distance) {
+                    winning = middle;
+                } else {
+                    losing = middle;
+                }
             }
+
+            winning
         }
+    };
+
+    // Every hold from the first winner up to its mirror image, time - first_winner, wins
+    time - 2 * first_winner + 1
+}
+
+fn quantify_winning_strategies(races: &[Race]) -> Vec<u64> {
+    races
+        .iter()
+        .map(|race| {
+            let quantity =
+                count_winning_strategies(u128::from(race.time), u128::from(race.distance));
+
+            // There can be no more winning strategies than milliseconds in the race
+            u64::try_from(quantity).unwrap()
+        })
+        .collect()
+}
+
+fn count_big_winning_strategies(time: &BigUint, distance: &BigUint) -> BigUint {
+    let beats_record = |hold: &BigUint| hold * (time - hold) > *distance;
+    let one = BigUint::from(1u8);
+
+    // As with count_winning_strategies, an unwinnable peak means an unwinnable race
+    let peak = time / 2u8;
+
+    if !beats_record(&peak) {
+        return BigUint::default();
+    }
+
+    // Big integers can take the discriminant exactly at any size. A winning peak
+    // means time^2 > 4 * distance, and the integer square root puts the estimate
+    // within a step of the first winning hold.
+    let discriminant = time * time - distance * 4u8;
+    let mut first_winner = (time - discriminant.sqrt()) / 2u8;
+
+    while first_winner > one && beats_record(&(&first_winner - &one)) {
+        first_winner -= &one;
+    }
+
+    while !beats_record(&first_winner) {
+        first_winner += &one;
     }
 
-    winning_strategies
+    time - first_winner * 2u8 + one
 }
 
-fn main() {
-    let races = parse_races("input.txt");
+// Converts the races to u64 values for the fast path, or None if any are too long for it
+fn narrow_races(races: &[Race<BigUint>]) -> Option<Vec<Race>> {
+    races
+        .iter()
+        .map(|race| {
+            Some(Race {
+                time: u64::try_from(&race.time).ok()?,
+                distance: u64::try_from(&race.distance).ok()?
+            })
+        })
+        .collect()
+}
+
+fn quantify_big_winning_strategies(races: &[Race<BigUint>]) -> Vec<BigUint> {
+    races
+        .iter()
+        .map(|race| count_big_winning_strategies(&race.time, &race.distance))
+        .collect()
+}
 
-    let winning_strategies = quantify_winning_strategies(&races);
+fn main() {
+    let races = parse_big_races("input.txt");
 
-    let product: u64 = winning_strategies.iter().product();
+    // Stick to the u64 fast path unless the kerned numbers are too long for it
+    let product: BigUint = match narrow_races(&races) {
+        Some(races) => quantify_winning_strategies(&races).iter().product::<u64>().into(),
+        None => quantify_big_winning_strategies(&races).iter().product()
+    };
 
-    println!("The product of all winning strategies is: {:?}", product);
+    println!("The product of all winning strategies is: {}", product);
 }
 
 #[cfg(test)]
@@ -85,13 +185,95 @@ mod tests {
     fn parses_races() {
         let expected: Vec<Race> = vec![Race {time: 71530, distance: 940200}];
 
-        assert_eq!(expected, parse_races("test.txt"))
+        assert_eq!(expected, narrow_races(&parse_big_races("test.txt")).unwrap())
     }
 
     #[test]
     fn correct_winning_strategies() {
         let expected = vec![71503];
 
-        assert_eq!(expected, quantify_winning_strategies(&parse_races("test.txt")))
+        assert_eq!(expected, quantify_winning_strategies(&narrow_races(&parse_big_races("test.txt")).unwrap()))
+    }
+
+    #[test]
+    fn unwinnable_race_has_no_strategies() {
+        assert_eq!(0, count_winning_strategies(4, 4));
+        assert_eq!(0, count_winning_strategies(0, 0));
+    }
+
+    #[test]
+    fn closed_form_matches_scan() {
+        for time in 0..64u128 {
+            for distance in 0..(time * time / 4 + 2) {
+                let expected = (0..=time).filter(|x| x * (time - x) > distance).count();
+
+                assert_eq!(expected as u128, count_winning_strategies(time, distance))
+            }
+        }
+    }
+
+    #[test]
+    fn handles_u128_sized_times() {
+        // Holding for a million ms, or a million ms short of the whole race, exactly ties the record
+        let time = 10u128.pow(12);
+
+        assert_eq!(time - 2_000_001, count_winning_strategies(time, 10u128.pow(18) - 10u128.pow(12)));
+
+        // Too long to square in a u128, so these are found by bisection
+        let time = 10u128.pow(30);
+
+        assert_eq!(time - 200_000_001, count_winning_strategies(time, 10u128.pow(38)));
+        assert_eq!(u128::MAX - 1, count_winning_strategies(u128::MAX, 0));
+    }
+
+    #[test]
+    fn parses_big_races() {
+        let expected: Vec<Race<BigUint>> = vec![Race {time: BigUint::from(71530u32), distance: BigUint::from(940200u32)}];
+
+        assert_eq!(expected, parse_big_races("test.txt"))
+    }
+
+    #[test]
+    fn big_winning_strategies_match_fast_path() {
+        let expected: Vec<BigUint> = quantify_winning_strategies(&narrow_races(&parse_big_races("test.txt")).unwrap())
+            .into_iter()
+            .map(BigUint::from)
+            .collect();
+
+        assert_eq!(expected, quantify_big_winning_strategies(&parse_big_races("test.txt")));
+
+        for time in 0..64u128 {
+            for distance in 0..(time * time / 4 + 2) {
+                assert_eq!(
+                    BigUint::from(count_winning_strategies(time, distance)),
+                    count_big_winning_strategies(&BigUint::from(time), &BigUint::from(distance))
+                )
+            }
+        }
+
+        let (time, distance) = (10u128.pow(30) + 7, 3 * 10u128.pow(37));
+
+        assert_eq!(
+            BigUint::from(count_winning_strategies(time, distance)),
+            count_big_winning_strategies(&BigUint::from(time), &BigUint::from(distance))
+        )
+    }
+
+    #[test]
+    fn narrows_races_that_fit_u64() {
+        let fits = Race {time: BigUint::from(u64::MAX), distance: BigUint::from(7u8)};
+        let too_long = Race {time: BigUint::from(u64::MAX) + 1u8, distance: BigUint::from(7u8)};
+
+        assert_eq!(Some(vec![Race {time: u64::MAX, distance: 7}]), narrow_races(&[fits]));
+        assert_eq!(None, narrow_races(&[too_long]))
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn handles_numbers_beyond_u128() {
+        let time = BigUint::from(10u8).pow(50);
+        let distance = BigUint::from(10u8).pow(60);
+        let expected = &time - BigUint::from(20_000_000_001u64);
+
+        assert_eq!(expected, count_big_winning_strategies(&time, &distance))
+    }
+}
@@ -23,7 +23,7 @@ fn parse_races(file_path: &str) -> Vec<Race> {
     match input.split_once('\n') {
         Some((a, b)) if a.starts_with("Time:") && b.starts_with("Distance:") => {
             // Reduce the slices to just the data
-            for value in a.split_once(':').unwrap().1.trim().split_whitespace() {
+            for value in a.split_once(':').unwrap().1.split_whitespace() {
                 let value = value.parse::<u32>().expect("There was an error parsing the value from the input");
                 let mut race = Race::empty();
                 race.time = value;
@@ -31,7 +31,7 @@ fn parse_races(file_path: &str) -> Vec<Race> {
                 races.push(race);
             }
 
-            for (index, value) in b.split_once(':').unwrap().1.trim().split_whitespace().enumerate() {
+            for (index, value) in b.split_once(':').unwrap().1.split_whitespace().enumerate() {
                 races[index].distance = value.parse::<u32>().unwrap();
             }
         },
@@ -41,32 +41,222 @@ fn parse_races(file_path: &str) -> Vec<Race> {
     races
 }
 
-fn quantify_winning_strategies(races: &Vec<Race>) -> Vec<u32> {
-    // The furthest distance achievable in any race is achieved at race.time / 2.
-    // The minimum winning distance achievable is at the value of x closest to 0 which satisfies
-    //     x * (race.time - x) > race.distance
-    // The total set of winning_strategies lies within the set of integers y that are bound by x < y < race.time - x
-    // This quantity is equal to ((race.time / 2) - x + 1) * 2  where race.time is an odd number, and
-    //     ((race.time / 2) - x + 1) * 2 - 1 where race.time is an even number
-    
-    let mut winning_strategies: Vec<u32> = Vec::new();
-
-    for race in races {
-        'current_race: for  x in 1..race.time / 2 {
-            if x * (race.time - x) > race.distance {
-                let quantity = match race.time {
-                    t if t % 2 != 0 => ((race.time / 2) - x + 1) * 2,
-                    _ => ((race.time / 2) - x + 1) * 2 - 1
-                };
-
-                winning_strategies.push(quantity);
-
-                break 'current_race;
+// Whether holding the button for `hold` ms beats the record distance. The product
+// can only overflow a u128 when it is far beyond any u128 record, so overflow wins.
+fn beats_record(hold: u128, time: u128, distance: u128) -> bool {
+    match hold.checked_mul(time - hold) {
+        Some(travelled) => travelled > distance,
+        None => true,
+    }
+}
+
+fn count_winning_strategies(time: u128, distance: u128) -> u128 {
+    // The distance travelled, x * (time - x), peaks at x = time / 2 and is symmetric
+    // about it, so a race that can't be won there can't be won at all.
+    let peak = time / 2;
+
+    if !beats_record(peak, time, distance) {
+        return 0;
+    }
+
+    // The first winning hold is just above the smaller root of x^2 - time * x + distance,
+    //     (time - sqrt(time^2 - 4 * distance)) / 2
+    // While time^2 fits in a u128 the discriminant is exact, and its integer square
+    // root leaves the estimate within a step of the first winning hold.
+    let first_winner = match time.checked_mul(time) {
+        Some(square) => {
+            let mut first_winner = (time - (square - 4 * distance).isqrt()) / 2;
+
+            while first_winner > 0 && beats_record(first_winner - 1, time, distance) {
+                first_winner -= 1;
+            }
+
+            while !beats_record(first_winner, time, distance) {
+                first_winner += 1;
+            }
+
+            first_winner
+        }
+        // Beyond that, bisect between holding for 0 ms, which never wins, and holding
+        // for `peak` ms, which does.
+        None => {
+            let (mut losing, mut winning) = (0, peak);
+
+            while winning - losing > 1 {
+                let middle = losing + (winning - losing) / 2;
+
+                if beats_record(middle, time, distance) {
+                    winning = middle;
+                } else {
+                    losing = middle;
+                }
             }
+
+            winning
+        }
+    };
+
+    // Every hold from the first winner up to its mirror image, time - first_winner, wins
+    time - 2 * first_winner + 1
+}
+
+fn quantify_winning_strategies(races: &[Race]) -> Vec<u32> {
+    races
+        .iter()
+        .map(|race| {
+            let quantity =
+                count_winning_strategies(u128::from(race.time), u128::from(race.distance));
+
+            // There can be no more winning strategies than milliseconds in the race
+            u32::try_from(quantity).unwrap()
+        })
+        .collect()
+}
+
+// A generalised boat. Holding the button adds `charge_rate` mm/ms of speed per ms
+// held, up to `max_speed`. Once released, drag removes `drag` of the boat's speed
+// every ms. Time passes in ticks of `tick` ms, so holds are whole numbers of ticks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BoatPhysics {
+    charge_rate: f64,
+    max_speed: f64,
+    drag: f64,
+    tick: f64,
+}
+
+impl BoatPhysics {
+    // The puzzle's boat: 1 mm/ms per ms held, with no speed limit and no drag
+    fn standard() -> Self {
+        Self {
+            charge_rate: 1.0,
+            max_speed: f64::INFINITY,
+            drag: 0.0,
+            tick: 1.0,
+        }
+    }
+
+    fn distance(&self, hold_ticks: u64, total_ticks: u64) -> f64 {
+        let speed = (self.charge_rate * hold_ticks as f64 * self.tick).min(self.max_speed);
+        let coasting_ticks = (total_ticks - hold_ticks) as f64;
+        let retained = (1.0 - self.drag * self.tick).max(0.0);
+
+        // Each tick covers speed * tick before drag scales the speed by `retained`,
+        // so the whole coast is a geometric series
+        if retained == 1.0 {
+            speed * self.tick * coasting_ticks
+        } else {
+            speed * self.tick * (1.0 - retained.powf(coasting_ticks)) / (1.0 - retained)
+        }
+    }
+}
+
+// The winning holds for a race, in ms
+#[derive(Debug, PartialEq)]
+struct HoldReport {
+    shortest: f64,
+    longest: f64,
+    strategies: u64,
+    optimal: f64,
+    best_distance: f64,
+}
+
+fn solve_race(race: &Race, physics: &BoatPhysics) -> Option<HoldReport> {
+    let total_ticks = (f64::from(race.time) / physics.tick).floor() as u64;
+    let record = f64::from(race.distance);
+    let distance = |hold_ticks: u64| physics.distance(hold_ticks, total_ticks);
+
+    // Speed only grows with the hold and the coast only shrinks, so the distance
+    // rises to a single peak and falls away again. Ternary search finds the peak.
+    let mut low = 0;
+    let mut high = total_ticks;
+
+    while high - low > 2 {
+        let left = low + (high - low) / 3;
+        let right = high - (high - low) / 3;
+
+        if distance(left) < distance(right) {
+            low = left + 1;
+        } else {
+            high = right;
+        }
+    }
+
+    let mut optimal = low;
+
+    for hold_ticks in low..=high {
+        if distance(hold_ticks) > distance(optimal) {
+            optimal = hold_ticks;
+        }
+    }
+
+    if distance(optimal) <= record {
+        return None;
+    }
+
+    // Either side of the peak the distance is monotonic, so bisect each side for
+    // the last losing hold
+    let (mut losing, mut winning) = (0, optimal);
+
+    while winning - losing > 1 {
+        let middle = losing + (winning - losing) / 2;
+
+        if distance(middle) > record {
+            winning = middle;
+        } else {
+            losing = middle;
+        }
+    }
+
+    let shortest = if distance(losing) > record { losing } else { winning };
+    let (mut winning, mut losing) = (optimal, total_ticks);
+
+    while losing - winning > 1 {
+        let middle = winning + (losing - winning) / 2;
+
+        if distance(middle) > record {
+            winning = middle;
+        } else {
+            losing = middle;
         }
     }
 
-    winning_strategies
+    let longest = if distance(losing) > record { losing } else { winning };
+
+    Some(HoldReport {
+        shortest: shortest as f64 * physics.tick,
+        longest: longest as f64 * physics.tick,
+        strategies: longest - shortest + 1,
+        optimal: optimal as f64 * physics.tick,
+        best_distance: distance(optimal),
+    })
+}
+
+// Reads `--charge-rate`, `--max-speed`, `--drag` and `--tick` values over the
+// standard boat, returning None when none were given
+fn parse_physics(args: &[String]) -> Option<BoatPhysics> {
+    let mut physics = BoatPhysics::standard();
+    let mut configured = false;
+
+    for pair in args.windows(2) {
+        let field = match pair[0].as_str() {
+            "--charge-rate" => &mut physics.charge_rate,
+            "--max-speed" => &mut physics.max_speed,
+            "--drag" => &mut physics.drag,
+            "--tick" => &mut physics.tick,
+            _ => continue,
+        };
+
+        *field = pair[1]
+            .parse::<f64>()
+            .unwrap_or_else(|_| panic!("{} expects a number, found {}", pair[0], pair[1]));
+        configured = true;
+    }
+
+    if physics.tick <= 0.0 {
+        panic!("--tick must be greater than zero");
+    }
+
+    configured.then_some(physics)
 }
 
 fn main() {
@@ -77,6 +267,25 @@ fn main() {
     let product: u32 = winning_strategies.iter().product();
 
     println!("The product of all winning strategies is: {:?}", product);
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if let Some(physics) = parse_physics(&args) {
+        for (index, race) in races.iter().enumerate() {
+            match solve_race(race, &physics) {
+                Some(report) => println!(
+                    "Race {}: hold {}-{} ms to win ({} strategies), best is {} ms for {} mm",
+                    index + 1,
+                    report.shortest,
+                    report.longest,
+                    report.strategies,
+                    report.optimal,
+                    report.best_distance
+                ),
+                None => println!("Race {}: cannot be won", index + 1),
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -96,4 +305,81 @@ mod tests {
 
         assert_eq!(expected, quantify_winning_strategies(&parse_races("test.txt")))
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn unwinnable_race_has_no_strategies() {
+        assert_eq!(0, count_winning_strategies(4, 4));
+        assert_eq!(0, count_winning_strategies(0, 0));
+    }
+
+    #[test]
+    fn closed_form_matches_scan() {
+        for time in 0..64u128 {
+            for distance in 0..(time * time / 4 + 2) {
+                let expected = (0..=time).filter(|x| x * (time - x) > distance).count();
+
+                assert_eq!(expected as u128, count_winning_strategies(time, distance))
+            }
+        }
+    }
+
+    #[test]
+    fn handles_u128_sized_times() {
+        // Holding for a million ms, or a million ms short of the whole race, exactly ties the record
+        let time = 10u128.pow(12);
+
+        assert_eq!(time - 2_000_001, count_winning_strategies(time, 10u128.pow(18) - 10u128.pow(12)));
+
+        // Too long to square in a u128, so these are found by bisection
+        let time = 10u128.pow(30);
+
+        assert_eq!(time - 200_000_001, count_winning_strategies(time, 10u128.pow(38)));
+        assert_eq!(u128::MAX - 1, count_winning_strategies(u128::MAX, 0));
+    }
+
+    #[test]
+    fn standard_physics_matches_closed_form() {
+        let physics = BoatPhysics::standard();
+
+        for time in 0..40 {
+            for distance in 0..(time * time / 4 + 2) {
+                let race = Race {time, distance};
+                let expected = count_winning_strategies(u128::from(time), u128::from(distance));
+                let strategies = solve_race(&race, &physics).map_or(0, |report| report.strategies);
+
+                assert_eq!(expected, u128::from(strategies))
+            }
+        }
+    }
+
+    #[test]
+    fn solves_races_with_speed_limit_and_drag() {
+        let race = Race {time: 7, distance: 9};
+
+        let mut physics = BoatPhysics::standard();
+        physics.max_speed = 3.0;
+
+        let expected = HoldReport {shortest: 2.0, longest: 3.0, strategies: 2, optimal: 3.0, best_distance: 12.0};
+
+        assert_eq!(Some(expected), solve_race(&race, &physics));
+
+        let mut physics = BoatPhysics::standard();
+        physics.drag = 0.5;
+
+        let expected = HoldReport {shortest: 5.0, longest: 5.0, strategies: 1, optimal: 5.0, best_distance: 7.5};
+
+        assert_eq!(Some(expected), solve_race(&Race {time: 7, distance: 7}, &physics));
+        assert_eq!(None, solve_race(&race, &physics))
+    }
+
+    #[test]
+    fn holds_are_whole_ticks() {
+        let mut physics = BoatPhysics::standard();
+        physics.tick = 2.0;
+
+        // Holding 2 or 4 ms both cover 8 mm, and odd holds fall between ticks
+        let expected = HoldReport {shortest: 2.0, longest: 4.0, strategies: 2, optimal: 2.0, best_distance: 8.0};
+
+        assert_eq!(Some(expected), solve_race(&Race {time: 6, distance: 7}, &physics))
+    }
+}
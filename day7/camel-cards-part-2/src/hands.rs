@@ -0,0 +1,412 @@
+use std::{cmp::Ordering, collections::HashMap, fs};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Card {
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
+    Ace,
+}
+
+impl Card {
+    pub fn from_label(c: char) -> Option<Self> {
+        let card = match c {
+            '2' => Card::Two,
+            '3' => Card::Three,
+            '4' => Card::Four,
+            '5' => Card::Five,
+            '6' => Card::Six,
+            '7' => Card::Seven,
+            '8' => Card::Eight,
+            '9' => Card::Nine,
+            'T' => Card::Ten,
+            'J' => Card::Jack,
+            'Q' => Card::Queen,
+            'K' => Card::King,
+            'A' => Card::Ace,
+            _ => return None,
+        };
+
+        Some(card)
+    }
+
+    pub fn label(&self) -> char {
+        // Cards are declared in the same order as their labels
+        "23456789TJQKA".as_bytes()[*self as usize] as char
+    }
+
+    // Every card from weakest to strongest under the standard rules
+    pub fn all() -> Vec<Card> {
+        "23456789TJQKA"
+            .chars()
+            .filter_map(Card::from_label)
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Suit {
+    Clubs,
+    Diamonds,
+    Hearts,
+    Spades,
+}
+
+impl Suit {
+    pub fn from_label(c: char) -> Option<Self> {
+        match c {
+            'C' => Some(Suit::Clubs),
+            'D' => Some(Suit::Diamonds),
+            'H' => Some(Suit::Hearts),
+            'S' => Some(Suit::Spades),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> char {
+        match self {
+            Suit::Clubs => 'C',
+            Suit::Diamonds => 'D',
+            Suit::Hearts => 'H',
+            Suit::Spades => 'S',
+        }
+    }
+}
+
+// Declared from weakest to strongest. Straights and flushes only occur when playing
+// with suited (poker) rules.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub enum HandType {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+    FiveOfAKind,
+}
+
+// The parts of Camel Cards which change from one variant of the game to another
+pub struct RuleSet {
+    // Cards from weakest to strongest, used to break ties between hands of the same type
+    pub ordering: Vec<Card>,
+    // Cards which stand in for whichever card makes the best hand
+    pub wildcards: Vec<Card>,
+    pub classifier: fn(&Hand, &RuleSet) -> HandType,
+    // Orders a hand's card strengths for comparison against hands of the same type
+    pub tiebreaker: fn(&[Card], &RuleSet) -> Vec<usize>,
+    // Whether each card is written with a suit, e.g. "KS", and hands separated by spaces
+    pub suited: bool,
+}
+
+impl RuleSet {
+    // Part 1 - no wildcards, and Jacks rank between Tens and Queens
+    pub fn standard() -> Self {
+        Self::with_wildcards(&[])
+    }
+
+    // Part 2 - Jacks become Jokers, which are wild but the weakest card individually
+    pub fn jokers() -> Self {
+        Self::with_wildcards(&[Card::Jack])
+    }
+
+    // Wildcards are moved below every other card, weakest first in the order given
+    pub fn with_wildcards(wildcards: &[Card]) -> Self {
+        let mut ordering = wildcards.to_vec();
+        ordering.extend(
+            Card::all()
+                .into_iter()
+                .filter(|card| !wildcards.contains(card)),
+        );
+
+        Self {
+            ordering,
+            wildcards: wildcards.to_vec(),
+            classifier: classify_by_groups,
+            tiebreaker: strengths_as_dealt,
+            suited: false,
+        }
+    }
+
+    // Standard 52-card poker, where ties are broken by the largest groups first
+    pub fn poker() -> Self {
+        Self {
+            ordering: Card::all(),
+            wildcards: Vec::new(),
+            classifier: classify_poker,
+            tiebreaker: strengths_by_group,
+            suited: true,
+        }
+    }
+
+    pub fn strength(&self, card: &Card) -> usize {
+        self.ordering
+            .iter()
+            .position(|c| c == card)
+            .expect("Every card should have a place in the ordering")
+    }
+
+    pub fn classify(&self, hand: &Hand) -> HandType {
+        (self.classifier)(hand, self)
+    }
+}
+
+// Determines the hand type from how many of each card the hand holds, with any
+// wildcards joining the largest group.
+fn classify_by_groups(hand: &Hand, rules: &RuleSet) -> HandType {
+    // We can use a HashMap to store the quantity of each card in the hand and use the size of the map and
+    // the values in the map to determine the hand type.
+    let mut hand_map: HashMap<Card, u32> = HashMap::new();
+    let mut wilds: u32 = 0;
+
+    for card in &hand.cards {
+        if rules.wildcards.contains(card) {
+            wilds += 1;
+        } else {
+            hand_map
+                .entry(*card)
+                .and_modify(|count| *count += 1)
+                .or_insert(1);
+        }
+    }
+
+    // Since wildcards count as whatever is most beneficial, we will add the quantity of wildcards to the
+    // quantity of the most frequent Card.
+    if let Some(most_frequent_value) = hand_map.values_mut().max() {
+        *most_frequent_value += wilds;
+    }
+
+    match hand_map {
+        // The first case needs to check for len() <= 1 since a hand of 5 wildcards is not a HighCard
+        m if m.len() <= 1 => HandType::FiveOfAKind,
+        m if m.len() == 2 && m.values().any(|count| *count == 4) => HandType::FourOfAKind,
+        m if m.len() == 2 => HandType::FullHouse,
+        m if m.len() == 3 && m.values().any(|count| *count == 3) => HandType::ThreeOfAKind,
+        m if m.len() == 3 => HandType::TwoPair,
+        m if m.len() == 4 => HandType::OnePair,
+        _ => HandType::HighCard,
+    }
+}
+
+// Counts each card in the hand, largest groups first and stronger cards first within
+// groups of the same size.
+fn group_cards(cards: &[Card], rules: &RuleSet) -> Vec<(u32, usize)> {
+    let mut counts: HashMap<usize, u32> = HashMap::new();
+
+    for card in cards {
+        *counts.entry(rules.strength(card)).or_insert(0) += 1;
+    }
+
+    let mut groups: Vec<(u32, usize)> = counts
+        .into_iter()
+        .map(|(strength, count)| (count, strength))
+        .collect();
+    groups.sort_by(|a, b| b.cmp(a));
+
+    groups
+}
+
+// Whether the hand is five consecutive cards. Aces may also play low, below the Two.
+// The strengths of a five-high straight, sorted, where the Ace plays low
+fn wheel(rules: &RuleSet) -> Vec<usize> {
+    let mut strengths: Vec<usize> = [Card::Two, Card::Three, Card::Four, Card::Five, Card::Ace]
+        .iter()
+        .map(|card| rules.strength(card))
+        .collect();
+    strengths.sort();
+
+    strengths
+}
+
+fn is_straight(cards: &[Card], rules: &RuleSet) -> bool {
+    let mut strengths: Vec<usize> = cards.iter().map(|card| rules.strength(card)).collect();
+    strengths.sort();
+    strengths.dedup();
+
+    strengths.len() == 5 && (strengths[4] - strengths[0] == 4 || strengths == wheel(rules))
+}
+
+fn classify_poker(hand: &Hand, rules: &RuleSet) -> HandType {
+    let counts: Vec<u32> = group_cards(&hand.cards, rules)
+        .into_iter()
+        .map(|(count, _)| count)
+        .collect();
+    let flush = hand.suits.len() == 5 && hand.suits.iter().all(|suit| *suit == hand.suits[0]);
+    let straight = is_straight(&hand.cards, rules);
+
+    match counts.as_slice() {
+        _ if straight && flush => HandType::StraightFlush,
+        [5] => HandType::FiveOfAKind,
+        [4, 1] => HandType::FourOfAKind,
+        [3, 2] => HandType::FullHouse,
+        _ if flush => HandType::Flush,
+        _ if straight => HandType::Straight,
+        [3, 1, 1] => HandType::ThreeOfAKind,
+        [2, 2, 1] => HandType::TwoPair,
+        [2, 1, 1, 1] => HandType::OnePair,
+        _ => HandType::HighCard,
+    }
+}
+
+// Camel Cards compares hands card by card in the order they were dealt.
+fn strengths_as_dealt(cards: &[Card], rules: &RuleSet) -> Vec<usize> {
+    cards.iter().map(|card| rules.strength(card)).collect()
+}
+
+// Poker compares the largest groups first, so a pair of Kings beats a pair of
+// Queens regardless of the kickers or the order the cards were dealt.
+fn strengths_by_group(cards: &[Card], rules: &RuleSet) -> Vec<usize> {
+    let mut strengths: Vec<usize> = group_cards(cards, rules)
+        .into_iter()
+        .flat_map(|(count, strength)| std::iter::repeat_n(strength, count as usize))
+        .collect();
+
+    // In a five-high straight the Ace plays low, so it moves behind the Five. Its
+    // own strength then only ever meets the Ace of another five-high straight.
+    let mut sorted = strengths.clone();
+    sorted.sort();
+
+    if sorted == wheel(rules) {
+        strengths.rotate_left(1);
+    }
+
+    strengths
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hand {
+    pub cards: Vec<Card>,
+    pub bid: u32,
+    pub rank: u32,
+    // Only filled in when playing with suited rules
+    pub suits: Vec<Suit>,
+    pub hand_type: HandType,
+    // Card strengths under the rules the hand was dealt with, in tiebreaking order
+    pub strengths: Vec<usize>,
+}
+
+impl Hand {
+    pub fn new(cards: &str, bid: u32, rules: &RuleSet) -> Self {
+        let mut instance = Self {
+            cards: Vec::new(),
+            bid,
+            rank: 0,
+            suits: Vec::new(),
+            hand_type: HandType::HighCard,
+            strengths: Vec::new(),
+        };
+
+        if rules.suited {
+            for label in cards.split_whitespace() {
+                let mut chars = label.chars();
+                let card = chars.next().and_then(Card::from_label);
+                let suit = chars.next().and_then(Suit::from_label);
+
+                match (card, suit, chars.next()) {
+                    (Some(card), Some(suit), None) => {
+                        instance.cards.push(card);
+                        instance.suits.push(suit);
+                    }
+                    _ => panic!("{} is not a valid card", label),
+                }
+            }
+        } else {
+            for c in cards.chars() {
+                let card =
+                    Card::from_label(c).unwrap_or_else(|| panic!("{} is not a valid card", c));
+
+                instance.cards.push(card);
+            }
+        }
+
+        instance.hand_type = rules.classify(&instance);
+        instance.strengths = (rules.tiebreaker)(&instance.cards, rules);
+
+        instance
+    }
+
+    // Hands are ordered by type first, then by the rules' tiebreaker. Different cards
+    // can still tie, such as two straights of the same height in poker, so this is
+    // kept apart from equality rather than implementing Ord.
+    pub fn compare(&self, other: &Self) -> Ordering {
+        self.hand_type
+            .cmp(&other.hand_type)
+            .then_with(|| self.strengths.cmp(&other.strengths))
+    }
+
+    pub fn label(&self) -> String {
+        if self.suits.is_empty() {
+            self.cards.iter().map(Card::label).collect()
+        } else {
+            let labels: Vec<String> = self
+                .cards
+                .iter()
+                .zip(&self.suits)
+                .map(|(card, suit)| format!("{}{}", card.label(), suit.label()))
+                .collect();
+
+            labels.join(" ")
+        }
+    }
+}
+
+pub fn parse_hands(path: &str, rules: &RuleSet) -> Vec<Hand> {
+    let input = fs::read_to_string(path).expect("Error reading file at specified path");
+    let mut hands: Vec<Hand> = Vec::new();
+
+    // Save an iteration over the dataset by determining hand type while parsing.
+    for line in input.lines() {
+        // The bid follows the last space, since suited hands contain spaces themselves
+        let hand = match line.rsplit_once(' ') {
+            Some((a, b)) => Hand::new(a, b.parse::<u32>().expect("Error parsing bid amount from hand."), rules),
+            _ => panic!("File contents were not formatted as expected. Please check file contents and try again."),
+        };
+
+        hands.push(hand);
+    }
+
+    hands
+}
+
+// Sorts the hands from weakest to strongest and ranks them in that order. Tied
+// hands can't be told apart by the rules, so they keep their input order and the
+// pairs of ranks they were given are returned for reporting.
+pub fn assign_rankings(hands: &mut [Hand]) -> Vec<(u32, u32)> {
+    hands.sort_by(Hand::compare);
+
+    let mut ties: Vec<(u32, u32)> = Vec::new();
+
+    for (index, hand) in hands.iter_mut().enumerate() {
+        hand.rank = index as u32 + 1;
+    }
+
+    for pair in hands.windows(2) {
+        if pair[0].compare(&pair[1]) == Ordering::Equal {
+            ties.push((pair[0].rank, pair[1].rank));
+        }
+    }
+
+    ties
+}
+
+pub fn calculate_winnings(hands: &[Hand]) -> u64 {
+    let mut sum: u64 = 0;
+
+    for hand in hands {
+        sum += u64::from(hand.bid) * u64::from(hand.rank)
+    }
+
+    sum
+}
@@ -1,258 +1,404 @@
-use std::{collections::HashMap, fs};
-
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-enum Card {
-    Joker,
-    Two,
-    Three,
-    Four,
-    Five,
-    Six,
-    Seven,
-    Eight,
-    Nine,
-    Ten,
-    Queen,
-    King,
-    Ace,
-}
+mod hands;
+
+use hands::*;
+use std::{collections::HashMap, env};
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-struct Hand {
+// The concrete hand a set of wildcards plays as
+#[derive(Debug, PartialEq, Eq)]
+struct Substitution {
     cards: Vec<Card>,
-    bid: u32,
-    rank: u32,
+    hand_type: HandType,
 }
 
-impl Hand {
-    fn new(cards: &str, bid: u32) -> Self {
-        let mut instance = Self {
-            cards: Vec::new(),
-            bid,
-            rank: 0,
-        };
-
-        for c in cards.chars() {
-            let card = match c {
-                'J' => Card::Joker,
-                '2' => Card::Two,
-                '3' => Card::Three,
-                '4' => Card::Four,
-                '5' => Card::Five,
-                '6' => Card::Six,
-                '7' => Card::Seven,
-                '8' => Card::Eight,
-                '9' => Card::Nine,
-                'T' => Card::Ten,
-                'Q' => Card::Queen,
-                'K' => Card::King,
-                'A' => Card::Ace,
-                _ => panic!("{} is not a valid card", c.to_string()),
-            };
-
-            instance.cards.push(card);
-        }
-
-        instance
+// Works out what the hand's wildcards stand in for, or None if it holds none. As in
+// classify_by_groups every wildcard joins the largest group, taking the strongest
+// card when groups are the same size; a hand of nothing but wildcards becomes the
+// strongest card which isn't wild.
+fn best_substitution(hand: &Hand, rules: &RuleSet) -> Option<Substitution> {
+    if !hand.cards.iter().any(|card| rules.wildcards.contains(card)) {
+        return None;
     }
-}
 
-impl PartialOrd for Hand {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.cards.partial_cmp(&other.cards)
-    }
-}
+    let mut hand_map: HashMap<Card, u32> = HashMap::new();
 
-impl Ord for Hand {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.cards.cmp(&other.cards)
+    for card in hand
+        .cards
+        .iter()
+        .filter(|card| !rules.wildcards.contains(card))
+    {
+        *hand_map.entry(*card).or_insert(0) += 1;
     }
-}
-
-#[derive(PartialEq, Eq, Debug, Hash)]
-enum HandType {
-    FiveOfAKind,
-    FourOfAKind,
-    FullHouse,
-    ThreeOfAKind,
-    TwoPair,
-    OnePair,
-    HighCard,
-}
-
-fn parse_hands(path: &str) -> HashMap<HandType, Vec<Hand>> {
-    let input = fs::read_to_string(path).expect("Error reading file at specified path");
-    let mut hands: HashMap<HandType, Vec<Hand>> = HashMap::new();
 
-    // Save an iteration over the dataset by determining hand type while parsing.
-    for line in input.lines() {
-        let hand;
-        match line.split_once(' ') {
-            Some((a, b)) => hand = Hand::new(a, b.parse::<u32>().expect("Error parsing bid amount from hand.")),
-            _ => panic!("File contents were not formatted as expected. Please check file contents and try again."),
-        }
-
-        // We can use a HashMap to store the quantity of each card in the hand and use the size of the map and
-        // the values in the map to determine the hand type.
-        let mut hand_map: HashMap<Card, u32> = HashMap::new();
-        let mut jokers: u32 = 0;
-
-        for card in hand.cards.iter() {
-            if let Card::Joker = card {
-                jokers += 1;
+    let replacement = hand_map
+        .iter()
+        .max_by_key(|(card, count)| (**count, rules.strength(card)))
+        .map(|(card, _)| *card)
+        .or_else(|| {
+            rules
+                .ordering
+                .iter()
+                .rev()
+                .find(|card| !rules.wildcards.contains(card))
+                .copied()
+        })
+        .expect("At least one card should not be wild");
+
+    let cards: Vec<Card> = hand
+        .cards
+        .iter()
+        .map(|card| {
+            if rules.wildcards.contains(card) {
+                replacement
             } else {
-                hand_map
-                    .entry(card.clone())
-                    .and_modify(|count| *count += 1)
-                    .or_insert(1);
-            }
-        }
-
-        // Since Jokers count as whatever is most beneficial, we will add the quantity of Jokers to the
-        // quantity of the most frequent Card.
-        let mut most_frequent_value = &mut std::u32::MIN;
-
-        for value in hand_map.values_mut() {
-            if *most_frequent_value < *value {
-                most_frequent_value = value;
+                *card
             }
-        }
-
-        *most_frequent_value = *most_frequent_value + jokers;
+        })
+        .collect();
+
+    // Once substituted there is nothing wild left, so the standard rules can classify it
+    let standard = RuleSet::standard();
+    let substituted = Hand {
+        cards: cards.clone(),
+        ..hand.clone()
+    };
+
+    Some(Substitution {
+        cards,
+        hand_type: standard.classify(&substituted),
+    })
+}
 
-        let hand_type = match hand_map {
-            // The first case needs to check for len() <= 1 since a hand of 5 Jokers is not a HighCard
-            m if m.len() <= 1 => HandType::FiveOfAKind,
-            m if m.len() == 2 && m.values().collect::<Vec<&u32>>().contains(&&4u32) => {
-                HandType::FourOfAKind
-            }
-            m if m.len() == 2 => HandType::FullHouse,
-            m if m.len() == 3 && m.values().collect::<Vec<&u32>>().contains(&&3u32) => {
-                HandType::ThreeOfAKind
-            }
-            m if m.len() == 3 => HandType::TwoPair,
-            m if m.len() == 4 => HandType::OnePair,
-            _ => HandType::HighCard,
-        };
+fn format_table(hands: &[Hand]) -> String {
+    let mut table = format!(
+        "{:>5}  {:<14}  {:<13}  {:>5}  {:>10}\n",
+        "Rank", "Hand", "Type", "Bid", "Winnings"
+    );
 
-        hands
-            .entry(hand_type)
-            .and_modify(|vec| vec.push(hand.clone()))
-            .or_insert(vec![hand]);
+    for hand in hands {
+        table.push_str(&format!(
+            "{:>5}  {:<14}  {:<13}  {:>5}  {:>10}\n",
+            hand.rank,
+            hand.label(),
+            format!("{:?}", hand.hand_type),
+            hand.bid,
+            u64::from(hand.bid) * u64::from(hand.rank)
+        ));
     }
 
-    hands
+    table
 }
 
-fn assign_rankings(hands: &mut HashMap<HandType, Vec<Hand>>) {
-    // Sort the vectors contained in the HashMap in order of lowest to highest.
-    for classified_hands in hands.values_mut() {
-        classified_hands.sort()
-    }
-
-    let mut rank: u32 = 1;
-    let hand_types_lowest_to_highest: Vec<HandType> = vec![
-        HandType::HighCard,
-        HandType::OnePair,
-        HandType::TwoPair,
-        HandType::ThreeOfAKind,
-        HandType::FullHouse,
-        HandType::FourOfAKind,
-        HandType::FiveOfAKind,
-    ];
-
-    for hand_type in hand_types_lowest_to_highest.into_iter() {
-        let hands_of_type = hands.get_mut(&hand_type);
-        if let Some(_) = hands_of_type {
-            let hands_of_type = hands_of_type.unwrap();
-
-            for hand in hands_of_type {
-                hand.rank = rank;
-                rank += 1;
+// Jokers are wild unless --standard is given, or --wild lists the wild card labels.
+// --poker scores suited poker hands instead, --table prints the full ranking and
+// --explain shows what each hand's wildcards were played as.
+fn parse_rules(args: &[String]) -> RuleSet {
+    let mut rules = RuleSet::jokers();
+
+    for (index, arg) in args.iter().enumerate() {
+        match arg.as_str() {
+            "--standard" => rules = RuleSet::standard(),
+            "--poker" => rules = RuleSet::poker(),
+            "--wild" => {
+                let labels = args.get(index + 1).map(String::as_str).unwrap_or("");
+                let wildcards: Vec<Card> = labels
+                    .chars()
+                    .map(|c| {
+                        Card::from_label(c).unwrap_or_else(|| panic!("{} is not a valid card", c))
+                    })
+                    .collect();
+
+                rules = RuleSet::with_wildcards(&wildcards);
             }
+            _ => (),
         }
     }
-}
 
-fn calculate_winnings(hands: HashMap<HandType, Vec<Hand>>) -> u64 {
-    let mut sum: u64 = 0;
+    rules
+}
 
-    for set_of_hands in hands.values() {
-        for hand in set_of_hands.into_iter() {
-            sum += u64::from(hand.bid * hand.rank)
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let rules = parse_rules(&args);
+    // Poker data won't be in the puzzle input, so --input reads hands from elsewhere
+    let path = args
+        .iter()
+        .position(|arg| arg == "--input")
+        .and_then(|index| args.get(index + 1))
+        .map_or("input.txt", String::as_str);
+    let mut hands = parse_hands(path, &rules);
+
+    if args.iter().any(|arg| arg == "--explain") {
+        for hand in &hands {
+            if let Some(substitution) = best_substitution(hand, &rules) {
+                let cards: String = substitution.cards.iter().map(Card::label).collect();
+
+                println!(
+                    "{} plays as {}, a {:?}",
+                    hand.label(),
+                    cards,
+                    substitution.hand_type
+                );
+            }
         }
     }
 
-    sum
-}
-
-fn main() {
-    let mut hands = parse_hands("input.txt");
+    for (rank, other_rank) in assign_rankings(&mut hands) {
+        eprintln!(
+            "warning: the hands ranked {} and {} are tied",
+            rank, other_rank
+        );
+    }
 
-    assign_rankings(&mut hands);
+    if args.iter().any(|arg| arg == "--table") {
+        print!("{}", format_table(&hands));
+    }
 
     println!(
         "The amount of winnings from the provided hands is: {:?}",
-        calculate_winnings(hands)
+        calculate_winnings(&hands)
     );
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cmp::Ordering;
 
     #[test]
     fn parses_hands() {
-        let mut expected: HashMap<HandType, Vec<Hand>> = HashMap::new();
+        let rules = RuleSet::jokers();
+        let expected = vec![
+            Hand::new("32T3K", 765, &rules),
+            Hand::new("T55J5", 684, &rules),
+            Hand::new("KK677", 28, &rules),
+            Hand::new("KTJJT", 220, &rules),
+            Hand::new("QQQJA", 483, &rules),
+        ];
+        let hand_types: Vec<HandType> = expected.iter().map(|hand| hand.hand_type).collect();
 
-        expected.insert(
-            HandType::FourOfAKind,
+        assert_eq!(
             vec![
-                Hand::new("T55J5", 684),
-                Hand::new("KTJJT", 220),
-                Hand::new("QQQJA", 483),
+                HandType::OnePair,
+                HandType::FourOfAKind,
+                HandType::TwoPair,
+                HandType::FourOfAKind,
+                HandType::FourOfAKind,
             ],
+            hand_types
         );
-        expected.insert(HandType::TwoPair, vec![Hand::new("KK677", 28)]);
-        expected.insert(HandType::OnePair, vec![Hand::new("32T3K", 765)]);
-
-        assert_eq!(expected, parse_hands("test.txt"))
+        assert_eq!(expected, parse_hands("test.txt", &RuleSet::jokers()))
     }
 
     #[test]
     fn assigns_rankings() {
-        let mut expected: HashMap<HandType, Vec<Hand>> = HashMap::new();
+        let rules = RuleSet::jokers();
+        let mut expected = vec![
+            Hand::new("32T3K", 765, &rules),
+            Hand::new("KK677", 28, &rules),
+            Hand::new("T55J5", 684, &rules),
+            Hand::new("QQQJA", 483, &rules),
+            Hand::new("KTJJT", 220, &rules),
+        ];
+
+        for (index, hand) in expected.iter_mut().enumerate() {
+            hand.rank = index as u32 + 1;
+        }
+
+        let mut actual = parse_hands("test.txt", &RuleSet::jokers());
 
-        let mut fours_of_a_kind = vec![
-            Hand::new("T55J5", 684),
-            Hand::new("QQQJA", 483),
-            Hand::new("KTJJT", 220),
+        assert_eq!(Vec::<(u32, u32)>::new(), assign_rankings(&mut actual));
+        assert_eq!(expected, actual)
+    }
+
+    #[test]
+    fn calculates_winnings() {
+        let mut actual = parse_hands("test.txt", &RuleSet::jokers());
+        assign_rankings(&mut actual);
+
+        assert_eq!(5905, calculate_winnings(&actual))
+    }
+
+    #[test]
+    fn reports_tied_hands() {
+        let rules = RuleSet::jokers();
+        let mut hands = vec![
+            Hand::new("KK677", 1, &rules),
+            Hand::new("32T3K", 2, &rules),
+            Hand::new("KK677", 3, &rules),
         ];
-        let mut two_pairs = vec![Hand::new("KK677", 28)];
-        let mut one_pair = vec![Hand::new("32T3K", 765)];
 
-        one_pair[0].rank = 1;
-        two_pairs[0].rank = 2;
-        fours_of_a_kind[0].rank = 3;
-        fours_of_a_kind[1].rank = 4;
-        fours_of_a_kind[2].rank = 5;
+        assert_eq!(vec![(2, 3)], assign_rankings(&mut hands));
+        // Tied hands keep the order they were dealt in
+        assert_eq!(
+            vec![2, 1, 3],
+            hands.iter().map(|hand| hand.bid).collect::<Vec<u32>>()
+        )
+    }
 
-        expected.insert(HandType::FourOfAKind, fours_of_a_kind);
-        expected.insert(HandType::TwoPair, two_pairs);
-        expected.insert(HandType::OnePair, one_pair);
+    #[test]
+    fn formats_ranked_table() {
+        let mut hands = parse_hands("test.txt", &RuleSet::jokers());
+        assign_rankings(&mut hands);
 
-        let mut actual = parse_hands("test.txt");
+        let table = format_table(&hands);
+        let lines: Vec<&str> = table.lines().collect();
 
+        assert_eq!(6, lines.len());
+        assert_eq!(
+            " Rank  Hand            Type             Bid    Winnings",
+            lines[0]
+        );
+        assert_eq!(
+            "    5  KTJJT           FourOfAKind      220        1100",
+            lines[5]
+        );
+    }
+
+    #[test]
+    fn calculates_standard_winnings() {
+        let mut actual = parse_hands("test.txt", &RuleSet::standard());
         assign_rankings(&mut actual);
 
-        assert_eq!(expected, actual)
+        assert_eq!(6440, calculate_winnings(&actual))
     }
 
     #[test]
-    fn calculates_winnings() {
-        let mut actual = parse_hands("test.txt");
+    fn supports_multiple_wildcards() {
+        let rules = RuleSet::with_wildcards(&[Card::Jack, Card::Two]);
+        let classify = |cards: &str| rules.classify(&Hand::new(cards, 0, &rules));
+
+        assert_eq!(HandType::FiveOfAKind, classify("J2J2J"));
+        assert_eq!(HandType::FourOfAKind, classify("A2JKA"));
+        assert_eq!(HandType::FullHouse, classify("KK2QQ"));
+        assert_eq!(HandType::OnePair, classify("3456J"));
+
+        // Wildcards are the weakest cards, in the order they were given
+        assert_eq!(
+            Ordering::Less,
+            Hand::new("J3333", 0, &rules).compare(&Hand::new("23333", 0, &rules))
+        );
+        assert_eq!(
+            Ordering::Less,
+            Hand::new("23333", 0, &rules).compare(&Hand::new("33333", 0, &rules))
+        );
+    }
+
+    #[test]
+    fn classifies_poker_hands() {
+        let rules = RuleSet::poker();
+        let classify = |cards: &str| rules.classify(&Hand::new(cards, 0, &rules));
+
+        assert_eq!(HandType::StraightFlush, classify("TH JH QH KH AH"));
+        assert_eq!(HandType::StraightFlush, classify("5D 4D 3D 2D AD"));
+        assert_eq!(HandType::FourOfAKind, classify("9C 9D 9H 9S 2C"));
+        assert_eq!(HandType::FullHouse, classify("3C 3D 3H KS KC"));
+        assert_eq!(HandType::Flush, classify("2S 7S 9S JS KS"));
+        assert_eq!(HandType::Straight, classify("6C 7D 8H 9S TC"));
+        assert_eq!(HandType::Straight, classify("AC 2D 3H 4S 5C"));
+        assert_eq!(HandType::ThreeOfAKind, classify("QC QD QH 4S 2C"));
+        assert_eq!(HandType::TwoPair, classify("QC QD 4H 4S 2C"));
+        assert_eq!(HandType::OnePair, classify("QC QD 5H 4S 2C"));
+        assert_eq!(HandType::HighCard, classify("KC QD 5H 4S 2C"));
+        assert_eq!(HandType::HighCard, classify("QC KD AH 2S 3C"));
+    }
+
+    #[test]
+    fn breaks_poker_ties_by_group() {
+        let rules = RuleSet::poker();
+        let hand = |cards: &str| Hand::new(cards, 0, &rules);
+
+        // The pair decides before the kickers, wherever it was dealt
+        assert_eq!(
+            Ordering::Less,
+            hand("AC 2D 2H 3S 4C").compare(&hand("3D 3C 2S 4H 5D"))
+        );
+        assert_eq!(
+            Ordering::Less,
+            hand("5H 5C 6S 7S KD").compare(&hand("2C 3S 8S 8D TD"))
+        );
+        // A five-high straight is the lowest straight
+        assert_eq!(
+            Ordering::Less,
+            hand("AC 2D 3H 4S 5C").compare(&hand("2C 3D 4H 5S 6C"))
+        );
+        // An Ace-high straight keeps its Ace on top
+        assert_eq!(
+            Ordering::Greater,
+            hand("AC KD QH JS TC").compare(&hand("KC QD JH TS 9C"))
+        );
+        assert_eq!(
+            rules.strength(&Card::Ace),
+            hand("AC KD QH JS TC").strengths[0]
+        );
+
+        // Straights of the same height tie without being the same hand
+        let mut hands = vec![hand("2C 3D 4H 5S 6C"), hand("6D 5C 4S 3H 2D")];
+
+        assert_eq!(Ordering::Equal, hands[0].compare(&hands[1]));
+        assert_ne!(hands[0], hands[1]);
+        assert_eq!(vec![(1, 2)], assign_rankings(&mut hands));
+    }
+
+    #[test]
+    fn calculates_poker_winnings() {
+        let mut actual = parse_hands("test-poker.txt", &RuleSet::poker());
         assign_rankings(&mut actual);
 
-        assert_eq!(5905, calculate_winnings(actual))
+        // From weakest: king high, ace high, flush, full house, straight flush
+        assert_eq!(36, calculate_winnings(&actual))
+    }
+
+    #[test]
+    fn explains_joker_substitutions() {
+        let rules = RuleSet::jokers();
+        let substitute = |cards: &str| {
+            best_substitution(&Hand::new(cards, 0, &rules), &rules).map(|substitution| {
+                let cards: String = substitution.cards.iter().map(Card::label).collect();
+
+                (cards, substitution.hand_type)
+            })
+        };
+
+        assert_eq!(None, substitute("32T3K"));
+        assert_eq!(
+            Some((String::from("T5555"), HandType::FourOfAKind)),
+            substitute("T55J5")
+        );
+        assert_eq!(
+            Some((String::from("KTTTT"), HandType::FourOfAKind)),
+            substitute("KTJJT")
+        );
+        assert_eq!(
+            Some((String::from("AAAAA"), HandType::FiveOfAKind)),
+            substitute("JJJJJ")
+        );
+        // Groups of the same size take the stronger card
+        assert_eq!(
+            Some((String::from("KKQQK"), HandType::FullHouse)),
+            substitute("KKQQJ")
+        );
+    }
+
+    #[test]
+    fn substitutions_match_classification() {
+        let rules = RuleSet::with_wildcards(&[Card::Jack, Card::Two]);
+
+        let labels: Vec<char> = "J2QA3".chars().collect();
+        let mut hands = parse_hands("test.txt", &rules);
+
+        // Every hand drawn from two wild and three plain labels
+        for index in 0..labels.len().pow(5) {
+            let cards: String = (0..5)
+                .map(|position| labels[index / labels.len().pow(position) % labels.len()])
+                .collect();
+
+            hands.push(Hand::new(&cards, 0, &rules));
+        }
+
+        for hand in hands {
+            if let Some(substitution) = best_substitution(&hand, &rules) {
+                assert_eq!(hand.hand_type, substitution.hand_type)
+            }
+        }
     }
 }
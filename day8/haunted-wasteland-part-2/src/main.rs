@@ -1,146 +1,576 @@
-use num_integer::Integer;
+use num_integer::{ExtendedGcd, Integer};
 use std::{collections::HashMap, fs};
 
 #[derive(Debug, PartialEq, Eq)]
-struct Node {
-    name: String,
-    left: String,
-    right: String,
+enum MapError {
+    Malformed(String),
+    // With no directions the ghosts could never take a step
+    NoDirections,
+    InvalidDirection(char),
+    DuplicateNode(String),
+    MissingNode {
+        name: String,
+        referenced_by: String,
+    },
+    ArityMismatch {
+        name: String,
+        expected: usize,
+        found: usize,
+    },
 }
 
-impl Node {
-    fn empty() -> Self {
-        Self {
-            name: String::from(""),
-            left: String::from(""),
-            right: String::from(""),
+impl std::fmt::Display for MapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MapError::Malformed(line) => write!(f, "Could not read a node from \"{}\"", line),
+            MapError::NoDirections => write!(f, "The map has no directions to follow"),
+            MapError::InvalidDirection(c) => {
+                write!(
+                    f,
+                    "Directions contained {:?}, which no child is labelled with",
+                    c
+                )
+            }
+            MapError::DuplicateNode(name) => write!(f, "Node {} is defined more than once", name),
+            MapError::MissingNode {
+                name,
+                referenced_by,
+            } => write!(
+                f,
+                "Node {} refers to {}, which is not defined",
+                referenced_by, name
+            ),
+            MapError::ArityMismatch {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Node {} has {} children, but earlier nodes have {}",
+                name, found, expected
+            ),
+        }
+    }
+}
+
+// Picks out starting or ending nodes, either with a pattern in which `*` matches any
+// run of characters and `?` any single character, or with an explicit list of names.
+#[derive(Debug, PartialEq, Eq)]
+enum NodeSelector {
+    Pattern(String),
+    Names(Vec<String>),
+}
+
+impl NodeSelector {
+    // Anything containing a wildcard is a pattern, otherwise a comma separated list
+    fn parse(s: &str) -> Self {
+        if s.contains(['*', '?']) {
+            NodeSelector::Pattern(String::from(s))
+        } else {
+            NodeSelector::Names(s.split(',').map(|name| String::from(name.trim())).collect())
         }
     }
 
-    fn new(name: &str, left: &str, right: &str) -> Self {
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            NodeSelector::Pattern(pattern) => {
+                let pattern: Vec<char> = pattern.chars().collect();
+                let name: Vec<char> = name.chars().collect();
+
+                glob_matches(&pattern, &name)
+            }
+            NodeSelector::Names(names) => names.iter().any(|n| n == name),
+        }
+    }
+}
+
+// Describes the selected nodes for messages, e.g. "nodes matching *Z" or "AAA or BBB"
+impl std::fmt::Display for NodeSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            NodeSelector::Pattern(pattern) => write!(f, "nodes matching {}", pattern),
+            NodeSelector::Names(names) => write!(f, "{}", names.join(" or ")),
+        }
+    }
+}
+
+fn glob_matches(pattern: &[char], name: &[char]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some('*'), _) => {
+            glob_matches(&pattern[1..], name)
+                || (!name.is_empty() && glob_matches(pattern, &name[1..]))
+        }
+        (Some('?'), Some(_)) => glob_matches(&pattern[1..], &name[1..]),
+        (Some(p), Some(n)) if p == n => glob_matches(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
+
+// How to read and walk a network. The nth letter of the alphabet is the instruction
+// to follow a node's nth child.
+#[derive(Debug, PartialEq, Eq)]
+struct Config {
+    alphabet: Vec<char>,
+    start: NodeSelector,
+    end: NodeSelector,
+}
+
+impl Default for Config {
+    // The puzzle's rules: L and R, starting on nodes ending in A and finishing on Z
+    fn default() -> Self {
         Self {
-            name: String::from(name),
-            left: String::from(left),
-            right: String::from(right),
+            alphabet: vec!['L', 'R'],
+            start: NodeSelector::Pattern(String::from("*A")),
+            end: NodeSelector::Pattern(String::from("*Z")),
         }
     }
 }
 
-fn parse_map(path: &str) -> (String, HashMap<String, Node>) {
-    let input = fs::read_to_string(path).unwrap();
+// The network with each node name interned to a dense id, in the order the nodes
+// were defined. Every node has `arity` children, stored flat in `children` so a
+// node's nth child is at `id * arity + n`, and walking the network needs no hashing
+// or allocation.
+#[derive(Debug, PartialEq, Eq)]
+struct Network {
+    names: Vec<String>,
+    ids: HashMap<String, u32>,
+    arity: usize,
+    children: Vec<u32>,
+}
 
-    // The first line of the input is the directions, and the contents thereafter
-    // are the network.
-    let destination;
-    let mut network: HashMap<String, Node> = HashMap::new();
-
-    match input.split_once('\n') {
-        Some((a, b)) => {
-            destination = String::from(a.trim());
-
-            for line in b.trim().lines() {
-                // Since we know the input format, we can process it based on the format.
-                let line = line.replace("= (", "");
-                let line = line.replace(",", "");
-                let line = line.replace(")", "");
-
-                let mut node = Node::empty();
-
-                for (i, val) in line.split_whitespace().enumerate() {
-                    match i {
-                        0 => node.name = String::from(val),
-                        1 => node.left = String::from(val),
-                        2 => node.right = String::from(val),
-                        _ => panic!("Index greater than 2 detected while parsing input."),
-                    }
-                }
+impl Network {
+    fn new(nodes: &[(&str, Vec<&str>)]) -> Result<Self, MapError> {
+        let mut network = Self {
+            names: Vec::new(),
+            ids: HashMap::new(),
+            arity: nodes.first().map_or(0, |(_, children)| children.len()),
+            children: Vec::new(),
+        };
+
+        // Intern every defined node before resolving children, since nodes can refer
+        // to nodes defined further down
+        for (name, children) in nodes {
+            if network.ids.contains_key(*name) {
+                return Err(MapError::DuplicateNode(String::from(*name)));
+            }
 
-                network.insert(node.name.clone(), node);
+            if children.len() != network.arity {
+                return Err(MapError::ArityMismatch {
+                    name: String::from(*name),
+                    expected: network.arity,
+                    found: children.len(),
+                });
             }
+
+            network
+                .ids
+                .insert(String::from(*name), network.names.len() as u32);
+            network.names.push(String::from(*name));
         }
+
+        for (name, children) in nodes {
+            for child in children {
+                let id = network.id(child).ok_or_else(|| MapError::MissingNode {
+                    name: String::from(*child),
+                    referenced_by: String::from(*name),
+                })?;
+
+                network.children.push(id);
+            }
+        }
+
+        Ok(network)
+    }
+
+    fn id(&self, name: &str) -> Option<u32> {
+        self.ids.get(name).copied()
+    }
+
+    fn name(&self, id: u32) -> &str {
+        &self.names[id as usize]
+    }
+
+    fn children_of(&self, id: u32) -> &[u32] {
+        let first = id as usize * self.arity;
+
+        &self.children[first..first + self.arity]
+    }
+
+    fn step(&self, id: u32, direction: usize) -> u32 {
+        self.children[id as usize * self.arity + direction]
+    }
+}
+
+fn parse_map(path: &str, alphabet: &[char]) -> Result<(Vec<usize>, Network), MapError> {
+    let input = fs::read_to_string(path).unwrap();
+
+    // The first line of the input is the directions, and the contents thereafter
+    // are the network.
+    let (directions, nodes) = match input.split_once('\n') {
+        Some((a, b)) => (a.trim(), b.trim()),
         _ => panic!(
             "File contents did not match expected format. Please check contents and try again."
         ),
+    };
+
+    let mut definitions: Vec<(&str, Vec<&str>)> = Vec::new();
+
+    for line in nodes.lines() {
+        // Since we know the input format, we can process it based on the format.
+        let definition = line.split_once(" = (").and_then(|(name, children)| {
+            let children = children.strip_suffix(')')?.split(',').map(str::trim);
+
+            Some((name.trim(), children.collect()))
+        });
+
+        match definition {
+            Some(definition) => definitions.push(definition),
+            None => return Err(MapError::Malformed(String::from(line))),
+        }
     }
 
-    (destination, network)
+    let network = Network::new(&definitions)?;
+
+    // Directions become indexes into a node's children
+    let directions = directions
+        .chars()
+        .map(|c| match alphabet.iter().position(|letter| *letter == c) {
+            Some(index) if index < network.arity => Ok(index),
+            _ => Err(MapError::InvalidDirection(c)),
+        })
+        .collect::<Result<Vec<usize>, MapError>>()?;
+
+    if directions.is_empty() {
+        return Err(MapError::NoDirections);
+    }
+
+    Ok((directions, network))
+}
+
+fn derive_starting_nodes(network: &Network, config: &Config) -> Vec<u32> {
+    // Ids follow the order nodes were defined, so the starting nodes do too
+    (0..network.names.len() as u32)
+        .filter(|id| config.start.matches(network.name(*id)))
+        .collect()
+}
+
+// Whether each node, by id, is an ending node
+fn derive_ending_nodes(network: &Network, config: &Config) -> Vec<bool> {
+    network
+        .names
+        .iter()
+        .map(|name| config.end.matches(name))
+        .collect()
 }
 
-fn derive_starting_nodes(network: &HashMap<String, Node>) -> Vec<String> {
-    let mut starting_nodes: Vec<String> = Vec::new();
+// When a ghost stands on an ending node. A ghost's position only depends on its node and
+// how far through the directions it is, so once that pair repeats the walk loops
+// forever. Before `cycle_start` the hits are one-offs; from `cycle_start` on the
+// hits in `cycle_hits` recur every `period` steps.
+#[derive(Debug, PartialEq, Eq)]
+struct GhostCycle {
+    cycle_start: u64,
+    period: u64,
+    transient_hits: Vec<u64>,
+    cycle_hits: Vec<u64>,
+}
+
+impl GhostCycle {
+    fn trace(directions: &[usize], network: &Network, start: u32, is_end: &[bool]) -> Self {
+        // The step each (node, direction index) state was first reached at
+        let mut first_seen: Vec<Option<u64>> = vec![None; network.names.len() * directions.len()];
+        let mut hits: Vec<u64> = Vec::new();
+        let mut current = start;
+        let mut time: u64 = 0;
+
+        loop {
+            let index = (time % directions.len() as u64) as usize;
+            let state = current as usize * directions.len() + index;
+
+            if let Some(cycle_start) = first_seen[state] {
+                let (transient_hits, cycle_hits) =
+                    hits.into_iter().partition(|hit| *hit < cycle_start);
+
+                return Self {
+                    cycle_start,
+                    period: time - cycle_start,
+                    transient_hits,
+                    cycle_hits,
+                };
+            }
+
+            first_seen[state] = Some(time);
 
-    // Iterate over network keys to find all nodes ending with 'A'
-    for key in network.keys() {
-        if key.ends_with('A') {
-            starting_nodes.push(key.clone());
+            if is_end[current as usize] {
+                hits.push(time);
+            }
+
+            current = network.step(current, directions[index]);
+            time += 1;
         }
     }
 
-    starting_nodes
+    fn at_end(&self, time: u64) -> bool {
+        if time < self.cycle_start {
+            self.transient_hits.contains(&time)
+        } else {
+            let lap_time = self.cycle_start + (time - self.cycle_start) % self.period;
+
+            self.cycle_hits.contains(&lap_time)
+        }
+    }
 }
 
+// Solves x = a (mod m) and x = b (mod n) for moduli which needn't be coprime,
+// returning the combined congruence, or None when the two can never agree.
+fn combine_congruences((a, m): (i128, i128), (b, n): (i128, i128)) -> Option<(i128, i128)> {
+    let ExtendedGcd { gcd, x, .. } = m.extended_gcd(&n);
+
+    if (b - a) % gcd != 0 {
+        return None;
+    }
+
+    // m * k = b - a (mod n) for k, dividing through by the gcd so m / gcd is invertible
+    let reduced = n / gcd;
+    let k = ((b - a) / gcd).mod_floor(&reduced) * x.mod_floor(&reduced) % reduced;
+    let lcm = m / gcd * n;
+
+    Some(((a + m * k).mod_floor(&lcm), lcm))
+}
+
+// The first step at which every ghost stands on an ending node at once, or None if
+// they never will.
 fn calculate_nodes_visited(
-    destination: String,
-    network: HashMap<String, Node>,
-    starting_nodes: Vec<String>,
-) -> u64 {
-    let mut visited_counter: u64 = 0;
-    let target_completed_paths = starting_nodes.len();
-    let mut current_keys = starting_nodes;
-    let mut complete_paths: Vec<u64> = Vec::new();
-
-    'outer: loop {
-        for step in destination.chars() {
-            let mut non_z_key_detected = false;
-            let mut new_keys: Vec<String> = Vec::new();
-
-            for key in current_keys.iter() {
-                if !key.ends_with('Z') {
-                    non_z_key_detected = true;
-                } else {
-                    complete_paths.push(visited_counter);
-
-                    if complete_paths.len() == target_completed_paths {
-                        break 'outer;
-                    } else {
-                        continue;
+    directions: &[usize],
+    network: &Network,
+    starting_nodes: &[u32],
+    is_end: &[bool],
+) -> Option<u128> {
+    let ghosts: Vec<GhostCycle> = starting_nodes
+        .iter()
+        .map(|start| GhostCycle::trace(directions, network, *start, is_end))
+        .collect();
+
+    // Until the slowest ghost settles into its cycle, any meeting has to be one of
+    // its one-off hits
+    let settled = match ghosts.iter().max_by_key(|ghost| ghost.cycle_start) {
+        Some(slowest) => {
+            for hit in &slowest.transient_hits {
+                if ghosts.iter().all(|ghost| ghost.at_end(*hit)) {
+                    return Some(u128::from(*hit));
+                }
+            }
+
+            slowest.cycle_start
+        }
+        None => return Some(0),
+    };
+
+    // After that, each ghost's hits are congruences over its period. A meeting needs
+    // one hit from every ghost to agree, so combine every choice of hits.
+    let mut congruences: Vec<(i128, i128)> = vec![(0, 1)];
+
+    for ghost in &ghosts {
+        let mut combined: Vec<(i128, i128)> = Vec::new();
+
+        for congruence in &congruences {
+            for hit in &ghost.cycle_hits {
+                let hit = (i128::from(*hit), i128::from(ghost.period));
+
+                if let Some(result) = combine_congruences(*congruence, hit) {
+                    if !combined.contains(&result) {
+                        combined.push(result);
                     }
                 }
+            }
+        }
 
-                let current_node = network.get(key).expect("Key/value pair mismatch detected.");
-                let new_key = match step {
-                    'L' => current_node.left.clone(),
-                    'R' => current_node.right.clone(),
-                    _ => panic!("Directions contained a value other than L or R"),
-                };
+        congruences = combined;
+    }
+
+    // The earliest step on or after `settled` satisfying any of the congruences
+    let settled = i128::from(settled);
+
+    congruences
+        .into_iter()
+        .map(|(residue, modulus)| {
+            residue + Integer::div_ceil(&(settled - residue).max(0), &modulus) * modulus
+        })
+        .min()
+        .map(|steps| steps as u128)
+}
+
+// The edges, as (node, direction) pairs, a ghost follows from its start until it
+// first reaches an ending node, or until it starts repeating itself if it never does.
+fn trace_path(
+    directions: &[usize],
+    network: &Network,
+    start: u32,
+    is_end: &[bool],
+) -> Vec<(u32, usize)> {
+    let mut seen: Vec<bool> = vec![false; network.names.len() * directions.len()];
+    let mut path: Vec<(u32, usize)> = Vec::new();
+    let mut current = start;
+
+    for (index, direction) in directions.iter().enumerate().cycle() {
+        let state = current as usize * directions.len() + index;
+
+        if is_end[current as usize] || seen[state] {
+            break;
+        }
+
+        seen[state] = true;
+        path.push((current, *direction));
+        current = network.step(current, *direction);
+    }
+
+    path
+}
+
+// Colours for the ghosts' paths, reused if there are more ghosts than colours
+const PATH_COLOURS: [&str; 6] = ["red", "blue", "darkgreen", "orange", "purple", "brown"];
+
+// Writes the network as a Graphviz digraph. Starting nodes are green and ending nodes
+// are red; each path given is drawn over the edges it follows in its own colour.
+fn export_dot(network: &Network, config: &Config, paths: &[Vec<(u32, usize)>]) -> String {
+    // The ghosts which walked each edge, indexed like `network.children`
+    let mut walked: Vec<Vec<usize>> = vec![Vec::new(); network.children.len()];
+
+    for (ghost, path) in paths.iter().enumerate() {
+        for (node, direction) in path {
+            let ghosts = &mut walked[*node as usize * network.arity + direction];
+
+            if !ghosts.contains(&ghost) {
+                ghosts.push(ghost);
+            }
+        }
+    }
 
-                new_keys.push(new_key);
+    let mut dot = String::from("digraph network {\n    node [shape=circle];\n");
+
+    for name in &network.names {
+        if config.start.matches(name) {
+            dot.push_str(&format!(
+                "    \"{}\" [style=filled, fillcolor=palegreen];\n",
+                name
+            ));
+        } else if config.end.matches(name) {
+            dot.push_str(&format!(
+                "    \"{}\" [style=filled, fillcolor=lightcoral];\n",
+                name
+            ));
+        }
+    }
+
+    for id in 0..network.names.len() as u32 {
+        let name = network.name(id);
+
+        // Children reached by several directions get a single edge labelled with all
+        // of them, e.g. LR
+        let mut edges: Vec<(u32, String, Vec<usize>)> = Vec::new();
+
+        for (direction, child) in network.children_of(id).iter().enumerate() {
+            let letter = config.alphabet.get(direction).copied().unwrap_or('?');
+
+            match edges.iter_mut().find(|(target, _, _)| target == child) {
+                Some((_, label, ghosts)) => {
+                    label.push(letter);
+                    ghosts.extend(&walked[id as usize * network.arity + direction]);
+                }
+                None => edges.push((
+                    *child,
+                    String::from(letter),
+                    walked[id as usize * network.arity + direction].clone(),
+                )),
             }
+        }
 
-            // If all keys end in Z, break the loop
-            if !non_z_key_detected {
-                break 'outer;
+        for (child, label, mut ghosts) in edges {
+            let child = network.name(child);
+
+            ghosts.sort();
+            ghosts.dedup();
+
+            if ghosts.is_empty() {
+                dot.push_str(&format!(
+                    "    \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                    name, child, label
+                ));
+            } else {
+                let colours: Vec<&str> = ghosts
+                    .iter()
+                    .map(|ghost| PATH_COLOURS[ghost % PATH_COLOURS.len()])
+                    .collect();
+
+                dot.push_str(&format!(
+                    "    \"{}\" -> \"{}\" [label=\"{}\", color=\"{}\", penwidth=2];\n",
+                    name,
+                    child,
+                    label,
+                    colours.join(":")
+                ));
             }
+        }
+    }
+
+    dot.push_str("}\n");
 
-            current_keys = new_keys;
-            visited_counter += 1;
+    dot
+}
+
+// Reads --alphabet, --start and --end over the default configuration
+fn parse_config(args: &[String]) -> Config {
+    let mut config = Config::default();
+
+    for pair in args.windows(2) {
+        match pair[0].as_str() {
+            "--alphabet" => config.alphabet = pair[1].chars().collect(),
+            "--start" => config.start = NodeSelector::parse(&pair[1]),
+            "--end" => config.end = NodeSelector::parse(&pair[1]),
+            _ => (),
         }
     }
 
-    complete_paths
-        .into_iter()
-        .reduce(|acc, x| acc.lcm(&x))
-        .unwrap()
+    config
 }
 
 fn main() {
-    let (destination, network) = parse_map("input.txt");
-    let starting_nodes = derive_starting_nodes(&network);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let config = parse_config(&args);
+    let (directions, network) =
+        parse_map("input.txt", &config.alphabet).unwrap_or_else(|error| panic!("{}", error));
+    let starting_nodes = derive_starting_nodes(&network, &config);
+    let is_end = derive_ending_nodes(&network, &config);
+
+    // --dot <file> writes the network out as a graph, and --paths adds each ghost's
+    // path from its starting node to it
+    if let Some(index) = args.iter().position(|arg| arg == "--dot") {
+        let path = args
+            .get(index + 1)
+            .expect("--dot expects a file to write to");
+        let paths: Vec<Vec<(u32, usize)>> = if args.iter().any(|arg| arg == "--paths") {
+            starting_nodes
+                .iter()
+                .map(|start| trace_path(&directions, &network, *start, &is_end))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        fs::write(path, export_dot(&network, &config, &paths)).expect("Error writing the graph");
+    }
 
-    println!(
-        "The number of steps required to reach ZZZ is: {:?}",
-        calculate_nodes_visited(destination, network, starting_nodes)
-    );
+    match calculate_nodes_visited(&directions, &network, &starting_nodes, &is_end) {
+        Some(steps) => println!(
+            "The number of steps required to reach {} is: {:?}",
+            config.end, steps
+        ),
+        None => println!(
+            "The ghosts will never all reach {} at the same time",
+            config.end
+        ),
+    }
 }
 
 #[cfg(test)]
@@ -149,46 +579,231 @@ mod tests {
 
     #[test]
     fn parses_map() {
-        let mut expected_map: HashMap<String, Node> = HashMap::new();
-        let network = vec![
-            Node::new("11A", "11B", "XXX"),
-            Node::new("11B", "XXX", "11Z"),
-            Node::new("11Z", "11B", "XXX"),
-            Node::new("22A", "22B", "XXX"),
-            Node::new("22B", "22C", "22C"),
-            Node::new("22C", "22Z", "22Z"),
-            Node::new("22Z", "22B", "22B"),
-            Node::new("XXX", "XXX", "XXX"),
-        ];
+        let network = Network::new(&[
+            ("11A", vec!["11B", "XXX"]),
+            ("11B", vec!["XXX", "11Z"]),
+            ("11Z", vec!["11B", "XXX"]),
+            ("22A", vec!["22B", "XXX"]),
+            ("22B", vec!["22C", "22C"]),
+            ("22C", vec!["22Z", "22Z"]),
+            ("22Z", vec!["22B", "22B"]),
+            ("XXX", vec!["XXX", "XXX"]),
+        ])
+        .unwrap();
+
+        assert_eq!([1, 7], network.children_of(0));
+        assert_eq!(Some(3), network.id("22A"));
+        assert_eq!(
+            Ok((vec![0, 1], network)),
+            parse_map("test.txt", &Config::default().alphabet)
+        )
+    }
 
-        for node in network.into_iter() {
-            expected_map.insert(node.name.clone(), node);
-        }
+    #[test]
+    fn reports_invalid_maps() {
+        let expected = Err(MapError::MissingNode {
+            name: String::from("YYY"),
+            referenced_by: String::from("11A"),
+        });
 
-        let expected = (String::from("LR"), expected_map);
+        assert_eq!(expected, Network::new(&[("11A", vec!["11A", "YYY"])]));
+    }
 
-        assert_eq!(expected, parse_map("test.txt"))
+    #[test]
+    fn rejects_maps_without_directions() {
+        assert_eq!(
+            Err(MapError::NoDirections),
+            parse_map("test-no-directions.txt", &Config::default().alphabet)
+        )
     }
 
     #[test]
     fn derives_starting_nodes() {
         let expected = vec!["11A", "22A"];
 
-        let (_, network) = parse_map("test.txt");
+        let (_, network) = parse_map("test.txt", &Config::default().alphabet).unwrap();
+        let starting_nodes: Vec<&str> = derive_starting_nodes(&network, &Config::default())
+            .into_iter()
+            .map(|id| network.name(id))
+            .collect();
 
-        assert_eq!(expected, derive_starting_nodes(&network))
+        assert_eq!(expected, starting_nodes)
     }
 
     #[test]
     fn calculates_nodes_visited() {
-        let (destination, network) = parse_map("test.txt");
+        let (directions, network) = parse_map("test.txt", &Config::default().alphabet).unwrap();
         assert_eq!(
-            6,
+            Some(6),
             calculate_nodes_visited(
-                destination,
-                network,
-                vec![String::from("11A"), String::from("22A")]
+                &directions,
+                &network,
+                &[network.id("11A").unwrap(), network.id("22A").unwrap()],
+                &derive_ending_nodes(&network, &Config::default())
             )
         )
     }
+
+    #[test]
+    fn synchronises_offset_cycles() {
+        // 1A reaches 1Z on steps 2, 6, 10, ... and 2A reaches 2Z on steps 1, 4, 7, ...
+        let network = Network::new(&[
+            ("1A", vec!["1B", "1B"]),
+            ("1B", vec!["1Z", "1Z"]),
+            ("1Z", vec!["1C", "1C"]),
+            ("1C", vec!["1A", "1A"]),
+            ("2A", vec!["2Z", "2Z"]),
+            ("2Z", vec!["2B", "2B"]),
+            ("2B", vec!["2A", "2A"]),
+        ])
+        .unwrap();
+        let starting_nodes = derive_starting_nodes(&network, &Config::default());
+        let is_end = derive_ending_nodes(&network, &Config::default());
+
+        assert_eq!(
+            Some(10),
+            calculate_nodes_visited(&[0], &network, &starting_nodes, &is_end)
+        )
+    }
+
+    #[test]
+    fn meets_during_transient_hits() {
+        // 1A only passes 1Z once, on step 1, before settling on 1B
+        let network = Network::new(&[
+            ("1A", vec!["1Z", "1Z"]),
+            ("1Z", vec!["1B", "1B"]),
+            ("1B", vec!["1B", "1B"]),
+            ("2A", vec!["2Z", "2Z"]),
+            ("2Z", vec!["2A", "2A"]),
+        ])
+        .unwrap();
+        let starting_nodes = derive_starting_nodes(&network, &Config::default());
+        let is_end = derive_ending_nodes(&network, &Config::default());
+
+        assert_eq!(
+            Some(1),
+            calculate_nodes_visited(&[0], &network, &starting_nodes, &is_end)
+        );
+
+        let ghost = GhostCycle::trace(&[0], &network, 0, &[false, true, false, false, true]);
+        let expected = GhostCycle {
+            cycle_start: 2,
+            period: 1,
+            transient_hits: vec![1],
+            cycle_hits: vec![],
+        };
+
+        assert_eq!(expected, ghost)
+    }
+
+    #[test]
+    fn reports_ghosts_that_never_meet() {
+        // 1A hits on odd steps and 2A on even steps
+        let network = Network::new(&[
+            ("1A", vec!["1Z", "1Z"]),
+            ("1Z", vec!["1B", "1B"]),
+            ("1B", vec!["1Z", "1Z"]),
+            ("2A", vec!["2B", "2B"]),
+            ("2B", vec!["2Z", "2Z"]),
+            ("2Z", vec!["2B", "2B"]),
+        ])
+        .unwrap();
+        let starting_nodes = derive_starting_nodes(&network, &Config::default());
+        let is_end = derive_ending_nodes(&network, &Config::default());
+
+        assert_eq!(
+            None,
+            calculate_nodes_visited(&[0], &network, &starting_nodes, &is_end)
+        )
+    }
+
+    #[test]
+    fn exports_dot() {
+        let network = Network::new(&[
+            ("AAA", vec!["BBB", "BBB"]),
+            ("BBB", vec!["AAA", "ZZZ"]),
+            ("ZZZ", vec!["ZZZ", "ZZZ"]),
+        ])
+        .unwrap();
+        let is_end = derive_ending_nodes(&network, &Config::default());
+        let paths = vec![trace_path(&[0, 0, 1], &network, 0, &is_end)];
+
+        assert_eq!(
+            vec![(0, 0), (1, 0), (0, 1), (1, 0), (0, 0), (1, 1)],
+            paths[0]
+        );
+
+        let expected = "digraph network {
+    node [shape=circle];
+    \"AAA\" [style=filled, fillcolor=palegreen];
+    \"ZZZ\" [style=filled, fillcolor=lightcoral];
+    \"AAA\" -> \"BBB\" [label=\"LR\", color=\"red\", penwidth=2];
+    \"BBB\" -> \"AAA\" [label=\"L\", color=\"red\", penwidth=2];
+    \"BBB\" -> \"ZZZ\" [label=\"R\", color=\"red\", penwidth=2];
+    \"ZZZ\" -> \"ZZZ\" [label=\"LR\"];
+}
+";
+
+        assert_eq!(expected, export_dot(&network, &Config::default(), &paths))
+    }
+
+    #[test]
+    fn walks_networks_with_other_alphabets() {
+        let config = Config {
+            alphabet: vec!['L', 'M', 'R'],
+            start: NodeSelector::parse("START, 2AB"),
+            end: NodeSelector::parse("*END*"),
+        };
+        let (directions, network) = parse_map("test-ternary.txt", &config.alphabet).unwrap();
+
+        assert_eq!(vec![1, 2, 0], directions);
+        assert_eq!([1, 2, 3], network.children_of(0));
+
+        let starting_nodes = derive_starting_nodes(&network, &config);
+        let is_end = derive_ending_nodes(&network, &config);
+
+        assert_eq!(vec![0, 4], starting_nodes);
+        assert_eq!(
+            Some(2),
+            calculate_nodes_visited(&directions, &network, &starting_nodes, &is_end)
+        );
+        // Letters beyond the number of children are as invalid as unknown ones
+        assert_eq!(
+            Err(MapError::InvalidDirection('M')),
+            parse_map("test-ternary.txt", &['L', 'R', 'X', 'M'])
+        );
+        assert_eq!(
+            Err(MapError::InvalidDirection('M')),
+            parse_map("test-ternary.txt", &['L', 'R'])
+        )
+    }
+
+    #[test]
+    fn selects_nodes() {
+        let pattern = NodeSelector::parse("1*Z");
+
+        assert!(pattern.matches("1Z"));
+        assert!(pattern.matches("1BZ"));
+        assert!(!pattern.matches("2BZ"));
+        assert!(!pattern.matches("1ZA"));
+        assert!(NodeSelector::parse("A?C").matches("ABC"));
+        assert!(!NodeSelector::parse("A?C").matches("AC"));
+        assert_eq!("nodes matching 1*Z", pattern.to_string());
+        assert_eq!("AAA or BBB", NodeSelector::parse("AAA, BBB").to_string());
+        assert_eq!(
+            NodeSelector::Names(vec![String::from("AAA"), String::from("BBB")]),
+            NodeSelector::parse("AAA,BBB")
+        );
+        assert_eq!(
+            Err(MapError::ArityMismatch {
+                name: String::from("BBB"),
+                expected: 2,
+                found: 3
+            }),
+            Network::new(&[
+                ("AAA", vec!["AAA", "BBB"]),
+                ("BBB", vec!["AAA", "BBB", "AAA"])
+            ])
+        )
+    }
 }
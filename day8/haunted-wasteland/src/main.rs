@@ -1,95 +1,151 @@
 use std::{collections::HashMap, fs};
 
-#[derive(Debug, PartialEq)]
-struct Node {
-    name: String,
-    left: String,
-    right: String
+#[derive(Debug, PartialEq, Eq)]
+enum MapError {
+    Malformed(String),
+    // With no directions there is never a step towards ZZZ
+    NoDirections,
+    InvalidDirection(char),
+    DuplicateNode(String),
+    MissingNode { name: String, referenced_by: String },
 }
 
-impl Node {
-    fn empty() -> Self {
-        Self {
-            name: String::from(""),
-            left: String::from(""),
-            right: String::from("")
+impl std::fmt::Display for MapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MapError::Malformed(line) => write!(f, "Could not read a node from \"{}\"", line),
+            MapError::NoDirections => write!(f, "The map has no directions to follow"),
+            MapError::InvalidDirection(c) => {
+                write!(f, "Directions contained {:?}, which is not L or R", c)
+            }
+            MapError::DuplicateNode(name) => write!(f, "Node {} is defined more than once", name),
+            MapError::MissingNode {
+                name,
+                referenced_by,
+            } => write!(f, "Node {} refers to {}, which is not defined", referenced_by, name),
         }
     }
+}
 
-    fn new(name: &str, left: &str, right: &str) -> Self {
-        Self {
-            name: String::from(name),
-            left: String::from(left),
-            right: String::from(right)
+// The network with each node name interned to a dense id, in the order the nodes
+// were defined. A node's left and right children are found by indexing `children`
+// with its id, so walking the network needs no hashing or allocation.
+#[derive(Debug, PartialEq, Eq)]
+struct Network {
+    names: Vec<String>,
+    ids: HashMap<String, u32>,
+    children: Vec<[u32; 2]>,
+}
+
+impl Network {
+    fn new(nodes: &[(&str, &str, &str)]) -> Result<Self, MapError> {
+        let mut network = Self {
+            names: Vec::new(),
+            ids: HashMap::new(),
+            children: Vec::new(),
+        };
+
+        // Intern every defined node before resolving children, since nodes can refer
+        // to nodes defined further down
+        for (name, _, _) in nodes {
+            if network.ids.contains_key(*name) {
+                return Err(MapError::DuplicateNode(String::from(*name)));
+            }
+
+            network.ids.insert(String::from(*name), network.names.len() as u32);
+            network.names.push(String::from(*name));
         }
+
+        for (name, left, right) in nodes {
+            let resolve = |child: &str| {
+                network.id(child).ok_or_else(|| MapError::MissingNode {
+                    name: String::from(child),
+                    referenced_by: String::from(*name),
+                })
+            };
+
+            let children = [resolve(left)?, resolve(right)?];
+            network.children.push(children);
+        }
+
+        Ok(network)
+    }
+
+    fn id(&self, name: &str) -> Option<u32> {
+        self.ids.get(name).copied()
+    }
+
+    fn step(&self, id: u32, direction: usize) -> u32 {
+        self.children[id as usize][direction]
     }
 }
 
-fn parse_map(path: &str) -> (String, HashMap<String, Node>) {
+fn parse_map(path: &str) -> Result<(Vec<usize>, Network), MapError> {
     let input = fs::read_to_string(path).unwrap();
 
     // The first line of the input is the directions, and the contents thereafter
     // are the network.
-    let destination;
-    let mut network: HashMap<String, Node> = HashMap::new();
-
-    match input.split_once('\n') {
-        Some((a, b)) => {
-            destination = String::from(a.trim());
-
-            for line in b.trim().lines() {
-                // Since we know the input format, we can process it based on the format.
-                let line = line.replace("= (", "");
-                let line = line.replace(",", "");
-                let line = line.replace(")", "");
-
-                let mut node = Node::empty();
-
-                for (i, val) in line.split_whitespace().enumerate() {
-                    match i {
-                        0 => node.name = String::from(val),
-                        1 => node.left = String::from(val),
-                        2 => node.right = String::from(val),
-                        _ => panic!("Index greater than 2 detected while parsing input."),
-                    }
-                }
-
-                network.insert(node.name.clone(), node);
-            }
-        },
-        _ => panic!("File contents did not match expected format. Please check contents and try again."),
+    let (directions, nodes) = match input.split_once('\n') {
+        Some((a, b)) => (a.trim(), b.trim()),
+        _ => panic!(
+            "File contents did not match expected format. Please check contents and try again."
+        ),
+    };
+
+    // Directions become indexes into a node's children
+    let directions = directions
+        .chars()
+        .map(|c| match c {
+            'L' => Ok(0),
+            'R' => Ok(1),
+            _ => Err(MapError::InvalidDirection(c)),
+        })
+        .collect::<Result<Vec<usize>, MapError>>()?;
+
+    if directions.is_empty() {
+        return Err(MapError::NoDirections);
     }
 
-    (destination, network)
-}
+    let mut definitions: Vec<(&str, &str, &str)> = Vec::new();
 
-fn calculate_nodes_visited(destination: String, network: HashMap<String, Node>) -> u32 {
-    let mut visited_counter:u32 = 0;
-    let mut current_key = "AAA";
+    for line in nodes.lines() {
+        // Since we know the input format, we can process it based on the format.
+        let definition = line.split_once(" = (").and_then(|(name, children)| {
+            let (left, right) = children.strip_suffix(')')?.split_once(", ")?;
 
-    'outer: while current_key != "ZZZ" {
-        for step in destination.chars() {
-            if current_key == "ZZZ" {
-                break 'outer;
-            }
+            Some((name.trim(), left.trim(), right.trim()))
+        });
 
-            let current_node = network.get(current_key).expect("Key/value pair mismatch detected.");
-            current_key = match step {
-                'L' => &current_node.left,
-                'R' => &current_node.right,
-                _ => panic!("Directions contained a value other than L or R"),
-            };
-            visited_counter += 1;
+        match definition {
+            Some(definition) => definitions.push(definition),
+            None => return Err(MapError::Malformed(String::from(line))),
         }
     }
 
+    Ok((directions, Network::new(&definitions)?))
+}
+
+fn calculate_nodes_visited(directions: &[usize], network: &Network) -> u32 {
+    let mut visited_counter: u32 = 0;
+    let mut current = network.id("AAA").expect("The network should contain AAA");
+    let destination = network.id("ZZZ").expect("The network should contain ZZZ");
+
+    for step in directions.iter().cycle() {
+        if current == destination {
+            break;
+        }
+
+        current = network.step(current, *step);
+        visited_counter += 1;
+    }
+
     visited_counter
 }
 
 fn main() {
-    let (destination, network) = parse_map("input.txt");
+    let (directions, network) = parse_map("input.txt").unwrap_or_else(|error| panic!("{}", error));
 
-    println!("The number of steps required to reach ZZZ is: {:?}", calculate_nodes_visited(destination, network));
+    println!("The number of steps required to reach ZZZ is: {:?}", calculate_nodes_visited(&directions, &network));
 }
 
 #[cfg(test)]
@@ -98,53 +154,63 @@ mod tests {
 
     #[test]
     fn parses_map_1() {
-        let mut expected_map: HashMap<String, Node> = HashMap::new();
-        let network = vec![
-            Node::new("AAA", "BBB", "CCC"),
-            Node::new("BBB", "DDD", "EEE"),
-            Node::new("CCC", "ZZZ", "GGG"),
-            Node::new("DDD", "DDD", "DDD"),
-            Node::new("EEE", "EEE", "EEE"),
-            Node::new("GGG", "GGG", "GGG"),
-            Node::new("ZZZ", "ZZZ", "ZZZ"),
-        ];
-
-        for node in network.into_iter() {
-            expected_map.insert(node.name.clone(), node);
-        }
-
-        let expected = (String::from("RL"), expected_map);
+        let network = Network::new(&[
+            ("AAA", "BBB", "CCC"),
+            ("BBB", "DDD", "EEE"),
+            ("CCC", "ZZZ", "GGG"),
+            ("DDD", "DDD", "DDD"),
+            ("EEE", "EEE", "EEE"),
+            ("GGG", "GGG", "GGG"),
+            ("ZZZ", "ZZZ", "ZZZ"),
+        ]);
+
+        let expected = Ok((vec![1, 0], network.unwrap()));
 
         assert_eq!(expected, parse_map("test1.txt"))
     }
 
     #[test]
     fn parses_map_2() {
-        let mut expected_map: HashMap<String, Node> = HashMap::new();
-        let network = vec![
-            Node::new("AAA", "BBB", "BBB"),
-            Node::new("BBB", "AAA", "ZZZ"),
-            Node::new("ZZZ", "ZZZ", "ZZZ"),
-        ];
-
-        for node in network.into_iter() {
-            expected_map.insert(node.name.clone(), node);
-        }
+        let network = Network::new(&[
+            ("AAA", "BBB", "BBB"),
+            ("BBB", "AAA", "ZZZ"),
+            ("ZZZ", "ZZZ", "ZZZ"),
+        ])
+        .unwrap();
+
+        assert_eq!(vec![[1, 1], [0, 2], [2, 2]], network.children);
+        assert_eq!("ZZZ", network.names[2]);
+        assert_eq!(Ok((vec![0, 0, 1], network)), parse_map("test2.txt"))
+    }
 
-        let expected = (String::from("LLR"), expected_map);
+    #[test]
+    fn reports_missing_nodes() {
+        let expected = Err(MapError::MissingNode {
+            name: String::from("CCC"),
+            referenced_by: String::from("BBB"),
+        });
+
+        assert_eq!(expected, Network::new(&[("AAA", "BBB", "BBB"), ("BBB", "AAA", "CCC")]));
+        assert_eq!(
+            Err(MapError::DuplicateNode(String::from("AAA"))),
+            Network::new(&[("AAA", "AAA", "AAA"), ("AAA", "AAA", "AAA")])
+        )
+    }
 
-        assert_eq!(expected, parse_map("test2.txt"))
+    #[test]
+    fn rejects_maps_without_directions() {
+        assert_eq!(Err(MapError::NoDirections), parse_map("test-no-directions.txt"))
     }
 
     #[test]
     fn calculates_nodes_visited_1() {
-        let (destination, network) = parse_map("test1.txt");
-        assert_eq!(2, calculate_nodes_visited(destination, network))
+        let (directions, network) = parse_map("test1.txt").unwrap();
+        assert_eq!(2, calculate_nodes_visited(&directions, &network))
     }
 
     #[test]
     fn calculates_nodes_visited_2() {
-        let (destination, network) = parse_map("test2.txt");
-        assert_eq!(6, calculate_nodes_visited(destination, network))
+        let (directions, network) = parse_map("test2.txt").unwrap();
+        assert_eq!(6, calculate_nodes_visited(&directions, &network))
     }
-}
\ No newline at end of file
+}
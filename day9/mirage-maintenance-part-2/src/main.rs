@@ -1,70 +1,418 @@
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{CheckedAdd, CheckedSub, One, Signed, Zero};
+use std::fmt::Display;
 use std::fs;
+use std::str::FromStr;
 
-fn parse_histories(path: &str) -> Vec<Vec<i32>> {
+// The integers a history can be read into. Arithmetic on them is checked, so a value
+// that does not fit is reported rather than wrapping around.
+trait Value: Clone + Zero + CheckedAdd + CheckedSub + FromStr + Display + Into<BigInt> {}
+
+impl<T: Clone + Zero + CheckedAdd + CheckedSub + FromStr + Display + Into<BigInt>> Value for T {}
+
+fn parse_histories<T: Value>(path: &str) -> Result<Vec<Vec<T>>, Overflow> {
     let input = fs::read_to_string(path).expect("There was an error reading the file");
-    let mut histories: Vec<Vec<i32>> = Vec::new();
+    let mut histories: Vec<Vec<T>> = Vec::new();
 
-    for line in input.lines() {
-        let mut history: Vec<i32> = Vec::new();
+    for (index, line) in input.lines().enumerate() {
+        let mut history: Vec<T> = Vec::new();
 
         for number in line.split_whitespace() {
-            history.push(number.parse().expect("The input only contain numbers"));
+            match number.parse() {
+                Ok(value) => history.push(value),
+                // A whole number that does not parse is too wide for the chosen integers
+                Err(_) if number.parse::<BigInt>().is_ok() => {
+                    return Err(Overflow::Value { line: index + 1 })
+                }
+                Err(_) => panic!("The input only contains whole numbers, found {:?}", number),
+            }
         }
 
         histories.push(history);
     }
 
-    histories
+    Ok(histories)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Overflow {
+    Value { line: usize },
+    History { line: usize },
+    Sum { line: usize },
 }
 
-fn predict_previous_value(history: Vec<i32>) -> i32 {
-    let mut patterns: Vec<Vec<i32>> = vec![history];
-    let mut current_index: usize = 0;
+impl std::fmt::Display for Overflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Overflow::Value { line } => write!(f, "a value on line {} does not fit", line),
+            Overflow::History { line } => write!(f, "the history on line {} overflowed", line),
+            Overflow::Sum { line } => {
+                write!(
+                    f,
+                    "the sum overflowed when adding the history on line {}",
+                    line
+                )
+            }
+        }
+    }
+}
+
+// A history alongside its rows of differences, down to the first row of zeros.
+#[derive(Debug, PartialEq, Eq)]
+struct DifferenceTable<T> {
+    rows: Vec<Vec<T>>,
+}
+
+impl<T: Value> DifferenceTable<T> {
+    // None when a difference does not fit in T.
+    fn new(history: &[T]) -> Option<Self> {
+        let mut rows: Vec<Vec<T>> = vec![history.to_vec()];
+
+        // Iterate over sliding windows to calculate the difference between them
+        const WINDOW_SIZE: usize = 2;
+
+        while rows.last().unwrap().iter().any(|value| !value.is_zero()) {
+            let current_pattern: Option<Vec<T>> = rows
+                .last()
+                .unwrap()
+                .windows(WINDOW_SIZE)
+                .map(|pair| pair[1].checked_sub(&pair[0]))
+                .collect();
+
+            rows.push(current_pattern?);
+        }
+
+        Some(Self { rows })
+    }
+
+    // The degree of the polynomial behind the history, which is the number of rows of
+    // differences it takes to reach a constant row. A history of zeros counts as 0.
+    fn degree(&self) -> usize {
+        self.rows.len().saturating_sub(2)
+    }
+
+    // Whether the differences settled into a row of zeros before running out of values.
+    // Any history is matched by some polynomial of degree length - 1, so only a lower
+    // degree says anything about the values that come next.
+    fn is_polynomial(&self) -> bool {
+        !self.rows.last().unwrap().is_empty()
+    }
+
+    // The next `steps` values of the history. Each row's next value is its last value
+    // plus the next value of the row beneath it, starting from the row of zeros.
+    fn extrapolate_forward(&self, steps: usize) -> Option<Vec<T>> {
+        let mut rows = self.rows.clone();
 
-    // Iterate over sliding windows to calculate the difference between them
-    const WINDOW_SIZE: usize = 2;
+        for _ in 0..steps {
+            let mut next = T::zero();
+
+            for row in rows.iter_mut().rev() {
+                next = next.checked_add(&row.last().cloned().unwrap_or_else(T::zero))?;
+                row.push(next.clone());
+            }
+        }
+
+        Some(rows[0].split_off(self.rows[0].len()))
+    }
+
+    // The `steps` values before the history, earliest first. Each row's previous value
+    // is its first value minus the previous value of the row beneath it.
+    fn extrapolate_backward(&self, steps: usize) -> Option<Vec<T>> {
+        let mut rows: Vec<Vec<T>> = self
+            .rows
+            .iter()
+            .map(|row| row.iter().rev().cloned().collect())
+            .collect();
+
+        for _ in 0..steps {
+            let mut previous = T::zero();
+
+            for row in rows.iter_mut().rev() {
+                previous = row
+                    .last()
+                    .cloned()
+                    .unwrap_or_else(T::zero)
+                    .checked_sub(&previous)?;
+                row.push(previous.clone());
+            }
+        }
+
+        let mut values = rows[0].split_off(self.rows[0].len());
+        values.reverse();
+
+        Some(values)
+    }
+}
 
-    'outer: loop {
-        let mut non_zero_detected = false;
-        let mut current_pattern: Vec<i32> = Vec::new();
+// Lays the table out as a triangle, with each difference between the two values it
+// was taken from.
+impl<T: Display> std::fmt::Display for DifferenceTable<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let width = self
+            .rows
+            .iter()
+            .flatten()
+            .map(|value| value.to_string().len())
+            .max()
+            .unwrap_or(1)
+            + 2;
 
-        for pair in patterns[current_index].windows(WINDOW_SIZE) {
-            let value = pair[1] - pair[0];
+        for (depth, row) in self.rows.iter().enumerate() {
+            write!(f, "{}", " ".repeat(depth * width / 2))?;
 
-            if value != 0 {
-                non_zero_detected = true;
+            for value in row {
+                write!(f, "{:^width$}", value, width = width)?;
             }
 
-            current_pattern.push(value);
+            writeln!(f)?;
         }
 
-        patterns.push(current_pattern);
-        current_index += 1;
+        Ok(())
+    }
+}
 
-        if !non_zero_detected {
-            break 'outer;
+#[derive(Debug, PartialEq, Eq)]
+enum FitError {
+    NotPolynomial { length: usize },
+}
+
+impl std::fmt::Display for FitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FitError::NotPolynomial { length } => write!(
+                f,
+                "the {} values are not a polynomial of degree below {}",
+                length,
+                length.saturating_sub(1)
+            ),
         }
     }
+}
+
+// The exact polynomial behind a history, with the first value at x = 0 and
+// coefficients listed from the constant term up.
+#[derive(Debug, PartialEq, Eq)]
+struct Polynomial {
+    coefficients: Vec<BigRational>,
+}
+
+impl Polynomial {
+    // Newton's forward form p(x) = sum of (difference k) * C(x, k), where each binomial
+    // C(x, k) = x (x - 1) ... (x - k + 1) / k! is expanded into plain coefficients.
+    fn fit<T: Value>(history: &[T]) -> Result<Self, FitError> {
+        let history: Vec<BigInt> = history.iter().cloned().map(Into::into).collect();
+        let table = DifferenceTable::new(&history).expect("Big integers do not overflow");
+
+        if !table.is_polynomial() {
+            return Err(FitError::NotPolynomial {
+                length: history.len(),
+            });
+        }
+
+        let mut coefficients: Vec<BigRational> = Vec::new();
+        let mut binomial: Vec<BigRational> = vec![BigRational::one()];
+
+        // The last row is all zeros and adds nothing
+        for (k, row) in table.rows[..table.rows.len() - 1].iter().enumerate() {
+            let difference = BigRational::from_integer(row[0].clone());
+
+            coefficients.resize(binomial.len(), BigRational::zero());
+
+            for (coefficient, term) in coefficients.iter_mut().zip(&binomial) {
+                *coefficient += &difference * term;
+            }
+
+            // C(x, k + 1) = C(x, k) * (x - k) / (k + 1)
+            let k = BigRational::from_integer(BigInt::from(k));
+            let divisor = &k + BigRational::one();
+            let mut next = vec![BigRational::zero(); binomial.len() + 1];
+
+            for (power, term) in binomial.iter().enumerate() {
+                next[power + 1] += term / &divisor;
+                next[power] -= term * &k / &divisor;
+            }
+
+            binomial = next;
+        }
 
-    // With the pattern vectors built out, the difference of the initial elements of two
-    // adjacent vectors is the predicted previous value of the lower-indexed vector.
-    let mut difference = 0;
+        Ok(Self { coefficients })
+    }
 
-    for pair in patterns.windows(WINDOW_SIZE).rev() {
-        difference = pair[0].first().unwrap() - difference;
+    fn evaluate(&self, x: &BigRational) -> BigRational {
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(BigRational::zero(), |acc, coefficient| {
+                acc * x + coefficient
+            })
     }
+}
+
+// Written highest power first, e.g. 1/2x^2 + 3/2x + 1.
+impl std::fmt::Display for Polynomial {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut written = false;
+
+        for (power, coefficient) in self.coefficients.iter().enumerate().rev() {
+            if coefficient.is_zero() {
+                continue;
+            }
+
+            let sign = match (written, coefficient.is_negative()) {
+                (false, false) => "",
+                (false, true) => "-",
+                (true, false) => " + ",
+                (true, true) => " - ",
+            };
+            let magnitude = coefficient.abs();
+
+            write!(f, "{}", sign)?;
+
+            if power == 0 || !magnitude.is_one() {
+                write!(f, "{}", magnitude)?;
+            }
 
-    difference
+            match power {
+                0 => {}
+                1 => write!(f, "x")?,
+                _ => write!(f, "x^{}", power)?,
+            }
+
+            written = true;
+        }
+
+        if !written {
+            write!(f, "0")?;
+        }
+
+        Ok(())
+    }
+}
+
+// Reads a position as an integer, a fraction like 5/2 or a decimal like 2.5.
+fn parse_position(text: &str) -> Option<BigRational> {
+    match text.split_once('.') {
+        Some((whole, fraction)) => {
+            let digits: BigInt = format!("{}{}", whole, fraction).parse().ok()?;
+            let scale = num_traits::pow(BigInt::from(10), fraction.len());
+
+            Some(BigRational::new(digits, scale))
+        }
+        None => text.parse().ok(),
+    }
+}
+
+fn predict_next_value<T: Value>(history: Vec<T>) -> Option<T> {
+    DifferenceTable::new(&history)?
+        .extrapolate_forward(1)
+        .map(|values| values[0].clone())
+}
+
+fn predict_previous_value<T: Value>(history: Vec<T>) -> Option<T> {
+    DifferenceTable::new(&history)?
+        .extrapolate_backward(1)
+        .map(|values| values[0].clone())
+}
+
+// Adds up one prediction per history, naming the first history whose prediction or
+// running total does not fit.
+fn sum_predictions<T: Value>(
+    histories: &[Vec<T>],
+    predict: fn(Vec<T>) -> Option<T>,
+) -> Result<T, Overflow> {
+    histories
+        .iter()
+        .enumerate()
+        .try_fold(T::zero(), |acc, (index, history)| {
+            let line = index + 1;
+            let prediction = predict(history.clone()).ok_or(Overflow::History { line })?;
+
+            acc.checked_add(&prediction).ok_or(Overflow::Sum { line })
+        })
+}
+
+fn report<T: Value>(args: &[String]) {
+    let histories: Vec<Vec<T>> = match parse_histories("input.txt") {
+        Ok(histories) => histories,
+        Err(overflow) => {
+            eprintln!(
+                "The histories could not be read: {}, try --integers i64, i128 or big",
+                overflow
+            );
+            std::process::exit(1);
+        }
+    };
+
+    // --table shows how each history was extrapolated
+    if args.iter().any(|arg| arg == "--table") {
+        for (index, history) in histories.iter().enumerate() {
+            match DifferenceTable::new(history) {
+                Some(table) => println!("Degree {}:\n{}", table.degree(), table),
+                None => eprintln!("{}\n", Overflow::History { line: index + 1 }),
+            }
+        }
+    }
+
+    // --fit prints the polynomial behind each history, --at X evaluates it at position X
+    let position = args
+        .windows(2)
+        .find(|pair| pair[0] == "--at")
+        .map(|pair| parse_position(&pair[1]).expect("--at takes a number such as 3, 5/2 or 2.5"));
+
+    if position.is_some() || args.iter().any(|arg| arg == "--fit") {
+        for (index, history) in histories.iter().enumerate() {
+            match (Polynomial::fit(history), &position) {
+                (Ok(polynomial), Some(x)) => println!(
+                    "History {}: p({}) = {}",
+                    index + 1,
+                    x,
+                    polynomial.evaluate(x)
+                ),
+                (Ok(polynomial), None) => println!("History {}: p(x) = {}", index + 1, polynomial),
+                (Err(error), _) => eprintln!("History {}: {}", index + 1, error),
+            }
+        }
+    }
+
+    let sums = [
+        ("next", sum_predictions(&histories, predict_next_value)),
+        (
+            "previous",
+            sum_predictions(&histories, predict_previous_value),
+        ),
+    ];
+
+    for (direction, sum) in sums {
+        match sum {
+            Ok(sum) => println!("The sum of all predicted {} values is: {}", direction, sum),
+            Err(overflow) => {
+                eprintln!(
+                    "The predicted {} values could not be summed: {}, try --integers i128 or big",
+                    direction, overflow
+                );
+                std::process::exit(1);
+            }
+        }
+    }
 }
 
 fn main() {
-    let histories = parse_histories("input.txt");
+    let args: Vec<String> = std::env::args().skip(1).collect();
 
-    let sum = histories
-        .into_iter()
-        .fold(0, |acc, x| acc + predict_previous_value(x));
+    // --integers picks how wide the values are read, from i32 up to unbounded
+    let integers = args
+        .windows(2)
+        .find(|pair| pair[0] == "--integers")
+        .map_or("i32", |pair| pair[1].as_str());
 
-    println!("The sum of all predicted previous values is: {:?}", sum);
+    match integers {
+        "i32" => report::<i32>(&args),
+        "i64" => report::<i64>(&args),
+        "i128" => report::<i128>(&args),
+        "big" => report::<BigInt>(&args),
+        _ => panic!("--integers takes one of i32, i64, i128 or big"),
+    }
 }
 
 #[cfg(test)]
@@ -79,12 +427,12 @@ mod tests {
             vec![10, 13, 16, 21, 30, 45],
         ];
 
-        assert_eq!(expected, parse_histories("test.txt"))
+        assert_eq!(Ok(expected), parse_histories::<i32>("test.txt"))
     }
 
     #[test]
     fn predicts_previous_value() {
-        let expected = vec![-3, 0, 5];
+        let expected = vec![Some(-3), Some(0), Some(5)];
         let actual = vec![
             predict_previous_value(vec![0, 3, 6, 9, 12, 15]),
             predict_previous_value(vec![1, 3, 6, 10, 15, 21]),
@@ -93,4 +441,160 @@ mod tests {
 
         assert_eq!(expected, actual)
     }
+
+    #[test]
+    fn predicts_next_value() {
+        let expected = vec![Some(18), Some(28), Some(68)];
+        let actual = vec![
+            predict_next_value(vec![0, 3, 6, 9, 12, 15]),
+            predict_next_value(vec![1, 3, 6, 10, 15, 21]),
+            predict_next_value(vec![10, 13, 16, 21, 30, 45]),
+        ];
+
+        assert_eq!(expected, actual)
+    }
+
+    #[test]
+    fn extrapolates_several_values() {
+        let table = DifferenceTable::new(&[1, 3, 6, 10, 15, 21]).unwrap();
+
+        assert_eq!(Some(vec![28, 36, 45]), table.extrapolate_forward(3));
+        assert_eq!(Some(vec![1, 0, 0]), table.extrapolate_backward(3));
+        assert_eq!(Some(Vec::new()), table.extrapolate_forward(0));
+
+        let table = DifferenceTable::new(&[10, 13, 16, 21, 30, 45]).unwrap();
+
+        assert_eq!(Some(vec![-4, 5]), table.extrapolate_backward(2));
+    }
+
+    #[test]
+    fn detects_degree() {
+        let degrees: Vec<usize> = parse_histories::<i32>("test.txt")
+            .unwrap()
+            .iter()
+            .map(|history| DifferenceTable::new(history).unwrap().degree())
+            .collect();
+
+        assert_eq!(vec![1, 2, 3], degrees);
+        assert_eq!(0, DifferenceTable::new(&[7, 7, 7]).unwrap().degree());
+        assert_eq!(0, DifferenceTable::new(&[0, 0]).unwrap().degree());
+    }
+
+    #[test]
+    fn displays_table() {
+        let expected = " 0  3  6  9 \n  3  3  3 \n    0  0 \n";
+
+        assert_eq!(
+            expected,
+            DifferenceTable::new(&[0, 3, 6, 9]).unwrap().to_string()
+        )
+    }
+
+    #[test]
+    fn fits_polynomials() {
+        let fitted: Vec<String> = parse_histories::<i32>("test.txt")
+            .unwrap()
+            .iter()
+            .map(|history| Polynomial::fit(history).unwrap().to_string())
+            .collect();
+
+        assert_eq!(
+            vec!["3x", "1/2x^2 + 3/2x + 1", "1/3x^3 - x^2 + 11/3x + 10"],
+            fitted
+        );
+        assert_eq!("0", Polynomial::fit(&[0, 0, 0_i32]).unwrap().to_string());
+        assert_eq!("-7", Polynomial::fit(&[-7, -7_i32]).unwrap().to_string());
+    }
+
+    #[test]
+    fn evaluates_polynomials() {
+        let polynomial = Polynomial::fit(&[1, 3, 6, 10, 15, 21_i32]).unwrap();
+
+        assert_eq!(
+            parse_position("15/8"),
+            Some(polynomial.evaluate(&parse_position("0.5").unwrap()))
+        );
+
+        for history in parse_histories::<i32>("test.txt").unwrap() {
+            let polynomial = Polynomial::fit(&history).unwrap();
+            let table = DifferenceTable::new(&history).unwrap();
+            let next = parse_position(&history.len().to_string()).unwrap();
+            let previous = parse_position("-1").unwrap();
+
+            assert_eq!(
+                BigRational::from_integer(table.extrapolate_forward(1).unwrap()[0].into()),
+                polynomial.evaluate(&next)
+            );
+            assert_eq!(
+                BigRational::from_integer(table.extrapolate_backward(1).unwrap()[0].into()),
+                polynomial.evaluate(&previous)
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_histories_without_polynomial() {
+        assert_eq!(
+            Err(FitError::NotPolynomial { length: 3 }),
+            Polynomial::fit(&[1, 2, 4_i32])
+        );
+        assert_eq!(
+            Err(FitError::NotPolynomial { length: 4 }),
+            Polynomial::fit(&[1, 2, 4, 8_i32])
+        );
+        assert!(Polynomial::fit(&[1, 2, 4, 7_i32]).is_ok());
+    }
+
+    #[test]
+    fn reports_overflowing_history() {
+        let histories: Vec<Vec<i32>> = vec![
+            vec![1, 2, 3],
+            vec![i32::MAX - 2, i32::MAX - 1, i32::MAX],
+            vec![4, 5],
+        ];
+
+        assert_eq!(
+            Err(Overflow::Value { line: 2 }),
+            parse_histories::<i32>("test-wide.txt")
+        );
+        assert!(parse_histories::<i64>("test-wide.txt").is_ok());
+        assert_eq!(None, predict_next_value(histories[1].clone()));
+        assert_eq!(None, DifferenceTable::new(&[i32::MIN, i32::MAX]));
+        assert_eq!(
+            Err(Overflow::History { line: 2 }),
+            sum_predictions(&histories, predict_next_value)
+        );
+        assert_eq!(
+            Err(Overflow::Sum { line: 3 }),
+            sum_predictions(
+                &[vec![1, 2], vec![i32::MAX, i32::MAX], vec![1]],
+                predict_previous_value
+            )
+        );
+    }
+
+    #[test]
+    fn predicts_with_wide_integers() {
+        let history = vec![i32::MAX as i128 - 2, i32::MAX as i128 - 1, i32::MAX as i128];
+
+        assert_eq!(Some(i32::MAX as i128 + 1), predict_next_value(history));
+
+        let histories: Vec<Vec<BigInt>> = parse_histories("test.txt").unwrap();
+        let steep: Vec<BigInt> = (1..5)
+            .map(|step| BigInt::from(u128::MAX) * step * step)
+            .collect();
+
+        assert_eq!(
+            Ok(BigInt::from(114)),
+            sum_predictions(&histories, predict_next_value)
+        );
+        assert_eq!(
+            Ok(BigInt::from(2)),
+            sum_predictions(&histories, predict_previous_value)
+        );
+        assert_eq!(
+            Some(BigInt::from(u128::MAX) * 25),
+            predict_next_value(steep)
+        );
+    }
 }